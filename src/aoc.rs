@@ -1,4 +1,9 @@
-/*pub(crate) mod day01a;
+use crate::aoc::solution::Solution;
+
+pub(crate) mod answers;
+pub(crate) mod bench;
+pub(crate) mod bits;
+pub(crate) mod day01a;
 pub(crate) mod day01b;
 pub(crate) mod day02a;
 pub(crate) mod day02b;
@@ -26,10 +31,8 @@ pub(crate) mod day13a;
 pub(crate) mod day13b;
 pub(crate) mod day14a;
 pub(crate) mod day14b;
-*/
 pub(crate) mod day15a;
 pub(crate) mod day15b;
-/*
 pub(crate) mod day16a;
 pub(crate) mod day16b;
 pub(crate) mod day17a;
@@ -37,10 +40,69 @@ pub(crate) mod day17b;
 pub(crate) mod day18a;
 pub(crate) mod day18b;
 pub(crate) mod day19a;
+pub(crate) mod day19b;
 pub(crate) mod day20a;
 pub(crate) mod day20b;
 pub(crate) mod day21a;
 pub(crate) mod day21b;
 pub(crate) mod day23a;
-*/
+pub(crate) mod day23b;
 pub mod file;
+pub(crate) mod grid;
+pub(crate) mod image;
+pub(crate) mod netpbm;
+pub(crate) mod packet;
+pub(crate) mod parse;
+pub(crate) mod shortest_path;
+pub(crate) mod snailfish;
+pub(crate) mod solution;
+pub(crate) mod space;
+
+pub(crate) fn solutions() -> Vec<&'static dyn Solution> {
+    vec![
+        &day01a::Day01a,
+        &day01b::Day01b,
+        &day02a::Day02a,
+        &day02b::Day02b,
+        &day03a::Day03a,
+        &day03b::Day03b,
+        &day04a::Day04a,
+        &day04b::Day04b,
+        &day05a::Day05a,
+        &day05b::Day05b,
+        &day06a::Day06a,
+        &day06b::Day06b,
+        &day07a::Day07a,
+        &day07b::Day07b,
+        &day08a::Day08a,
+        &day08b::Day08b,
+        &day09a::Day09a,
+        &day09b::Day09b,
+        &day10a::Day10a,
+        &day10b::Day10b,
+        &day11a::Day11a,
+        &day11b::Day11b,
+        &day12a::Day12a,
+        &day12b::Day12b,
+        &day13a::Day13a,
+        &day13b::Day13b,
+        &day14a::Day14a,
+        &day14b::Day14b,
+        &day15a::Day15a,
+        &day15b::Day15b,
+        &day16a::Day16a,
+        &day16b::Day16b,
+        &day17a::Day17a,
+        &day17b::Day17b,
+        &day18a::Day18a,
+        &day18b::Day18b,
+        &day19a::Day19a,
+        &day19b::Day19b,
+        &day20a::Day20a,
+        &day20b::Day20b,
+        &day21a::Day21a,
+        &day21b::Day21b,
+        &day23a::Day23a,
+        &day23b::Day23b,
+    ]
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::Answer;
+
+/// Name of the answers file in an input directory.
+pub const ANSWERS_FILE: &str = "answers.txt";
+
+/// The expected answers for one set of inputs, by puzzle name like "15b".
+///
+/// The file has one answer per line, "<name> <answer>". Line breaks in a
+/// text answer are written as "\n"; lines starting with '#' are comments.
+/// Blanks at the end of the lines of a text answer do not count, so that
+/// editors trimming them do not change the answer.
+pub struct Answers {
+    answers: HashMap<String, Answer>,
+}
+
+/// How a computed answer compares to the expected one.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: Answer },
+    Unknown,
+}
+
+impl Answers {
+    /// No expected answers at all; everything verifies as unknown.
+    pub fn empty() -> Answers {
+        Answers {
+            answers: HashMap::new(),
+        }
+    }
+
+    pub fn parse(input: &str) -> Result<Answers, ParseError> {
+        let mut answers = HashMap::new();
+        for (no, line) in parse::lines(input) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, answer) = line
+                .split_once(' ')
+                .ok_or_else(|| ParseError::new(no, 1, "a puzzle name and its answer"))?;
+            let answer = match answer.parse::<i128>() {
+                Ok(n) => Answer::Number(n),
+                Err(_) => Answer::Text(answer.replace("\\n", "\n")),
+            };
+            answers.insert(String::from(name), answer);
+        }
+        Ok(Answers { answers })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Answer> {
+        self.answers.get(name)
+    }
+
+    pub fn verify(&self, name: &str, actual: &Answer) -> Verdict {
+        match self.get(name) {
+            None => Verdict::Unknown,
+            Some(expected) if same(expected, actual) => Verdict::Pass,
+            Some(expected) => Verdict::Fail {
+                expected: expected.clone(),
+            },
+        }
+    }
+}
+
+fn same(expected: &Answer, actual: &Answer) -> bool {
+    match (expected, actual) {
+        (Answer::Text(e), Answer::Text(a)) => e
+            .lines()
+            .map(str::trim_end)
+            .eq(a.lines().map(str::trim_end)),
+        _ => expected == actual,
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "pass"),
+            Verdict::Fail { expected } => write!(f, "FAIL, expected {}", expected),
+            Verdict::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Solves `s` on its input in resources/input and asserts that the answer is
+/// the one in resources/input/answers.txt.
+#[cfg(test)]
+pub fn check(s: &dyn crate::aoc::solution::Solution) {
+    use crate::aoc::file;
+
+    let answers = Answers::parse(&file::read_input(ANSWERS_FILE).unwrap()).unwrap();
+    if answers.get(&s.name()).is_none() {
+        panic!("no answer for {} in {}", s.name(), ANSWERS_FILE);
+    }
+    let actual = s
+        .solve(&file::read_input(&s.input_file()).unwrap())
+        .unwrap();
+    println!("result : {}", actual);
+    assert_eq!(answers.verify(&s.name(), &actual), Verdict::Pass);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_answers() {
+        let a = Answers::parse("# comment\n01a 1692\n\n13b #  #\\n####\n").unwrap();
+        assert_eq!(a.get("01a"), Some(&Answer::Number(1692)));
+        assert_eq!(
+            a.get("13b"),
+            Some(&Answer::Text(String::from("#  #\n####")))
+        );
+        assert_eq!(a.get("01b"), None);
+    }
+
+    #[test]
+    fn missing_answer() {
+        let e = Answers::parse("01a 1692\n01b").err().unwrap();
+        assert_eq!(e.to_string(), "2:1 expected a puzzle name and its answer");
+    }
+
+    #[test]
+    fn verdicts() {
+        let a = Answers::parse("01a 1692").unwrap();
+        assert_eq!(a.verify("01a", &Answer::Number(1692)), Verdict::Pass);
+        assert_eq!(
+            a.verify("01a", &Answer::Number(1693)),
+            Verdict::Fail {
+                expected: Answer::Number(1692)
+            }
+        );
+        assert_eq!(a.verify("01b", &Answer::Number(1724)), Verdict::Unknown);
+    }
+
+    #[test]
+    fn trailing_blanks() {
+        let a = Answers::parse("13b #  #\\n## \n").unwrap();
+        let picture = |s: &str| Answer::Text(String::from(s));
+        assert_eq!(a.verify("13b", &picture("#  #\n##  ")), Verdict::Pass);
+        assert_eq!(a.verify("13b", &picture("#  #\n##")), Verdict::Pass);
+        assert_ne!(a.verify("13b", &picture("#  #\n #")), Verdict::Pass);
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{Solution, SolveError};
+
+/// Fastest, median and slowest of a number of runs.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+pub struct Timings {
+    pub parse: Stats,
+    pub solve: Stats,
+}
+
+/// Median parse and solve time by puzzle name, as saved by an earlier run.
+pub type Baseline = HashMap<String, (Duration, Duration)>;
+
+impl Stats {
+    /// Panics if there are no samples.
+    pub fn of(mut samples: Vec<Duration>) -> Stats {
+        samples.sort();
+        Stats {
+            min: samples[0],
+            median: samples[samples.len() / 2],
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// Parses and solves `input` `runs` times, each run solving what it parsed.
+pub fn bench(s: &dyn Solution, input: &str, runs: usize) -> Result<Timings, SolveError> {
+    let mut parse = Vec::with_capacity(runs);
+    let mut solve = Vec::with_capacity(runs);
+    for _ in 0..runs.max(1) {
+        let start = Instant::now();
+        let parsed = s.parse(input)?;
+        parse.push(start.elapsed());
+        let start = Instant::now();
+        s.solve_parsed(parsed)?;
+        solve.push(start.elapsed());
+    }
+    Ok(Timings {
+        parse: Stats::of(parse),
+        solve: Stats::of(solve),
+    })
+}
+
+/// One line per puzzle: name, median parse and median solve time in
+/// nanoseconds.
+pub fn save_baseline(path: &Path, results: &[(String, Timings)]) -> io::Result<()> {
+    let mut s = String::new();
+    for (name, t) in results {
+        s += &format!(
+            "{} {} {}\n",
+            name,
+            t.parse.median.as_nanos(),
+            t.solve.median.as_nanos()
+        );
+    }
+    fs::write(path, s)
+}
+
+pub fn read_baseline(input: &str) -> Result<Baseline, ParseError> {
+    let mut baseline = Baseline::new();
+    for (no, line) in parse::lines(input) {
+        let fields: Vec<&str> = line.split(' ').collect();
+        if fields.len() != 3 {
+            return Err(ParseError::new(no, 1, "name, parse and solve time"));
+        }
+        let column = fields[0].len() + 2;
+        let parse: u64 = parse::number(fields[1], column).map_err(|e| e.on_line(no))?;
+        let column = column + fields[1].len() + 1;
+        let solve: u64 = parse::number(fields[2], column).map_err(|e| e.on_line(no))?;
+        baseline.insert(
+            String::from(fields[0]),
+            (Duration::from_nanos(parse), Duration::from_nanos(solve)),
+        );
+    }
+    Ok(baseline)
+}
+
+/// The timings of one puzzle, and how the medians changed since `baseline`.
+pub fn report(name: &str, t: &Timings, baseline: Option<&(Duration, Duration)>) -> String {
+    let mut s = format!("{}  parse {}  solve {}", name, t.parse, t.solve);
+    if let Some((parse, solve)) = baseline {
+        s += &format!(
+            "  vs baseline: parse {}, solve {}",
+            change(*parse, t.parse.median),
+            change(*solve, t.solve.median)
+        );
+    }
+    s
+}
+
+fn change(before: Duration, now: Duration) -> String {
+    if before.is_zero() {
+        return String::from("n/a");
+    }
+    let percent = (now.as_secs_f64() / before.as_secs_f64() - 1.0) * 100.0;
+    format!("{:+.0}%", percent)
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "min {:?} median {:?} max {:?}",
+            self.min, self.median, self.max
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn stats() {
+        let s = Stats::of(vec![ms(5), ms(1), ms(3), ms(9), ms(2)]);
+        assert_eq!(s.min, ms(1));
+        assert_eq!(s.median, ms(3));
+        assert_eq!(s.max, ms(9));
+    }
+
+    #[test]
+    fn baseline_round_trip() {
+        let t = Timings {
+            parse: Stats::of(vec![ms(1)]),
+            solve: Stats::of(vec![ms(20)]),
+        };
+        let path = std::env::temp_dir().join("aoc2021-baseline-test.txt");
+        save_baseline(&path, &[(String::from("15b"), t)]).unwrap();
+        let baseline = read_baseline(&fs::read_to_string(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(baseline["15b"], (ms(1), ms(20)));
+    }
+
+    #[test]
+    fn bad_baseline() {
+        let e = read_baseline("15a 100 200\n15b 100 2x0").unwrap_err();
+        assert_eq!(e.to_string(), "2:9 expected a number, found '2x0'");
+    }
+
+    #[test]
+    fn report_against_baseline() {
+        let t = Timings {
+            parse: Stats::of(vec![ms(1)]),
+            solve: Stats::of(vec![ms(30)]),
+        };
+        let line = report("12b", &t, Some(&(ms(1), ms(20))));
+        assert!(
+            line.ends_with("vs baseline: parse +0%, solve +50%"),
+            "{}",
+            line
+        );
+    }
+}
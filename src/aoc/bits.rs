@@ -0,0 +1,249 @@
+use std::io::{self, BufReader, Bytes, Read};
+
+use crate::aoc::parse::{self, ParseError};
+
+/// Reads a bit stream, most significant bit first, from hex digits like the
+/// day 16 transmissions, from raw bytes, or from anything that can be read.
+///
+/// Errors give the column of the hex digit or byte they were found in.
+pub struct BitReader<'a> {
+    source: Source<'a>,
+    /// Bits taken from the source but not yet read, in the lowest
+    /// `buffered` bits.
+    buffer: u64,
+    buffered: usize,
+    bits_read: usize,
+}
+
+enum Source<'a> {
+    Hex(&'a str),
+    Bytes(&'a [u8]),
+    Read(Bytes<BufReader<Box<dyn Read + 'a>>>),
+}
+
+impl<'a> BitReader<'a> {
+    pub fn from_hex(hex: &'a str) -> BitReader<'a> {
+        BitReader::new(Source::Hex(hex))
+    }
+
+    pub fn from_bytes(bytes: &'a [u8]) -> BitReader<'a> {
+        BitReader::new(Source::Bytes(bytes))
+    }
+
+    /// Streams the bytes of `reader`, which need not fit in memory.
+    pub fn from_reader<R: Read + 'a>(reader: R) -> BitReader<'a> {
+        let reader: Box<dyn Read + 'a> = Box::new(reader);
+        BitReader::new(Source::Read(BufReader::new(reader).bytes()))
+    }
+
+    fn new(source: Source<'a>) -> BitReader<'a> {
+        BitReader {
+            source,
+            buffer: 0,
+            buffered: 0,
+            bits_read: 0,
+        }
+    }
+
+    pub fn bits_read(&self) -> usize {
+        self.bits_read
+    }
+
+    /// Bits left to read, if the source knows; a hex source assumes that the
+    /// rest are all hex digits.
+    pub fn remaining(&self) -> Option<usize> {
+        let rest = match &self.source {
+            Source::Hex(hex) => 4 * hex.len(),
+            Source::Bytes(bytes) => 8 * bytes.len(),
+            Source::Read(_) => return None,
+        };
+        Some(self.buffered + rest)
+    }
+
+    /// The column of the hex digit or byte the last bit was read from.
+    pub fn column(&self) -> usize {
+        self.bits_read.div_ceil(self.unit())
+    }
+
+    /// Fails unless `n` more bits are left for `what`. Passes if the source
+    /// does not know how much is left.
+    pub fn require(&self, n: usize, what: &str) -> Result<(), ParseError> {
+        match self.remaining() {
+            Some(remaining) if n > remaining => {
+                let expected = format!("{}, found only {} bits", what, remaining);
+                Err(ParseError::at(self.column() + 1, &expected))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn read_bit(&mut self) -> Result<u64, ParseError> {
+        self.read_bits(1)
+    }
+
+    /// The next `n` bits, at most 64, as a number.
+    pub fn read_bits(&mut self, n: usize) -> Result<u64, ParseError> {
+        assert!(n <= 64, "cannot read {} bits at once", n);
+        if n > 32 {
+            // keeps the buffer from overflowing while it fills
+            let high = self.read_bits(n - 32)?;
+            return Ok(high << 32 | self.read_bits(32)?);
+        }
+        while self.buffered < n {
+            let (bits, len) = self.next_unit()?;
+            self.buffer = self.buffer << len | bits;
+            self.buffered += len;
+        }
+        self.buffered -= n;
+        let value = self.buffer >> self.buffered;
+        self.buffer &= (1 << self.buffered) - 1;
+        self.bits_read += n;
+        Ok(value)
+    }
+
+    /// Bits per hex digit or byte.
+    fn unit(&self) -> usize {
+        match self.source {
+            Source::Hex(_) => 4,
+            Source::Bytes(_) | Source::Read(_) => 8,
+        }
+    }
+
+    /// The bits of the next hex digit or byte, and how many there are.
+    fn next_unit(&mut self) -> Result<(u64, usize), ParseError> {
+        // the column the unit would be in
+        let column = (self.bits_read + self.buffered) / self.unit() + 1;
+        let end = || ParseError::at(column, "more bits of the packet");
+        match &mut self.source {
+            Source::Hex(hex) => {
+                let c = hex.chars().next().ok_or_else(end)?;
+                *hex = &hex[c.len_utf8()..];
+                Ok((parse::digit(c, 16, column)? as u64, 4))
+            }
+            Source::Bytes(bytes) => {
+                let (&b, rest) = bytes.split_first().ok_or_else(end)?;
+                *bytes = rest;
+                Ok((b as u64, 8))
+            }
+            Source::Read(bytes) => match bytes.next() {
+                None => Err(end()),
+                Some(Ok(b)) => Ok((b as u64, 8)),
+                Some(Err(e)) => Err(read_error(column, e)),
+            },
+        }
+    }
+}
+
+fn read_error(column: usize, e: io::Error) -> ParseError {
+    ParseError::at(column, &format!("more bits of the packet ({})", e))
+}
+
+/// Collects bits, most significant first, to turn them into hex digits.
+#[cfg(test)]
+pub struct BitWriter {
+    bits: Vec<bool>,
+}
+
+#[cfg(test)]
+impl BitWriter {
+    pub fn new() -> Self {
+        BitWriter { bits: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// The lowest `n` bits of `value`.
+    pub fn write_bits(&mut self, value: u64, n: usize) {
+        for i in (0..n).rev() {
+            self.bits.push((value >> i) & 1 == 1);
+        }
+    }
+
+    pub fn append(&mut self, other: &BitWriter) {
+        self.bits.extend(&other.bits);
+    }
+
+    /// Hex digits, padded with zeros to whole bytes.
+    pub fn to_hex(&self) -> String {
+        self.bytes().iter().map(|b| format!("{:02X}", b)).collect()
+    }
+
+    /// The bits as bytes, the last one padded with zeros.
+    pub fn bytes(&self) -> Vec<u8> {
+        self.bits
+            .chunks(8)
+            .map(|byte| (0..8).fold(0, |b, i| b << 1 | byte.get(i).map_or(0, |&bit| bit as u8)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hex_and_bytes_agree() {
+        let mut hex = BitReader::from_hex("D2FE28");
+        let mut bytes = BitReader::from_bytes(&[0xD2, 0xFE, 0x28]);
+        for n in [3, 3, 5, 5, 5, 3] {
+            assert_eq!(hex.read_bits(n).unwrap(), bytes.read_bits(n).unwrap());
+        }
+        assert_eq!(hex.bits_read(), 24);
+        assert_eq!(bytes.remaining(), Some(0));
+    }
+
+    #[test]
+    fn wide_reads() {
+        let mut r = BitReader::from_hex("0123456789ABCDEF0123");
+        assert_eq!(r.read_bits(4).unwrap(), 0);
+        assert_eq!(r.read_bits(64).unwrap(), 0x123456789ABCDEF0);
+        assert_eq!(r.remaining(), Some(12));
+        assert_eq!(r.read_bits(12).unwrap(), 0x123);
+    }
+
+    #[test]
+    fn from_reader() {
+        let mut r = BitReader::from_reader(&[0xD2, 0xFE, 0x28][..]);
+        assert_eq!(r.read_bits(6).unwrap(), 0b110100);
+        assert_eq!(r.remaining(), None);
+        assert_eq!(r.read_bits(18).unwrap(), 0b101111111000101000);
+        let e = r.read_bit().unwrap_err();
+        assert_eq!(e.to_string(), "1:4 expected more bits of the packet");
+    }
+
+    #[test]
+    fn column_of_bad_digit() {
+        let mut r = BitReader::from_hex("D2XE28");
+        assert_eq!(r.read_bits(8).unwrap(), 0xD2);
+        assert_eq!(r.column(), 2);
+        let e = r.read_bit().unwrap_err();
+        assert_eq!(e.to_string(), "1:3 expected a hex digit, found 'X'");
+    }
+
+    #[test]
+    fn require() {
+        let mut r = BitReader::from_hex("D2F");
+        r.read_bits(3).unwrap();
+        assert!(r.require(9, "the rest").is_ok());
+        let e = r.require(10, "the rest").unwrap_err();
+        assert_eq!(e.to_string(), "1:2 expected the rest, found only 9 bits");
+    }
+
+    #[test]
+    fn write_and_read_back() {
+        let mut w = BitWriter::new();
+        w.write_bits(0b110, 3);
+        w.write_bits(0x2F, 7);
+        assert_eq!(w.to_hex(), "CBC0");
+        assert_eq!(w.bytes(), vec![0xCB, 0xC0]);
+        w.write_bits(0, 2);
+        assert_eq!(w.to_hex(), "CBC0");
+        w.write_bits(1, 1);
+        assert_eq!(w.to_hex(), "CBC8");
+        let mut r = BitReader::from_hex("CBC8");
+        assert_eq!(r.read_bits(3).unwrap(), 0b110);
+        assert_eq!(r.read_bits(7).unwrap(), 0x2F);
+    }
+}
@@ -1,26 +1,44 @@
-use std::path::Path;
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
 
-use crate::aoc::file;
+pub(crate) struct Day01a;
 
-pub(crate) fn solve() -> i32 {
-    solve_file(&file::input("input01.txt"))
+impl Solution for Day01a {
+    fn day(&self) -> u32 {
+        1
+    }
+
+    fn part(&self) -> char {
+        'a'
+    }
+
+    fn title(&self) -> &'static str {
+        "Sonar Sweep"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_input(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_numbers(solution::unbox(parsed)).into())
+    }
 }
 
-fn solve_file(f: &Path) -> i32 {
-    let numbers = read_input(f);
-    let count = count_increase(&numbers);
-    count
+fn solve_numbers(numbers: Vec<i32>) -> i32 {
+    count_increase(&numbers)
 }
 
-fn read_input(f: &Path) -> Vec<i32> {
-    let lines = file::read_lines(f).unwrap();
+fn read_input(input: &str) -> Result<Vec<i32>, ParseError> {
     let mut numbers = Vec::new();
-    for line in lines {
-        let line = line.unwrap();
-        let number: i32 = line.parse().unwrap();
+    for (no, line) in parse::lines(input) {
+        let number: i32 = parse::number(line, 1).map_err(|e| e.on_line(no))?;
         numbers.push(number);
     }
-    numbers
+    if numbers.is_empty() {
+        return Err(ParseError::new(1, 1, "a number"));
+    }
+    Ok(numbers)
 }
 
 fn count_increase(numbers: &Vec<i32>) -> i32 {
@@ -28,7 +46,7 @@ fn count_increase(numbers: &Vec<i32>) -> i32 {
     let mut now = numbers[0];
     for num in numbers {
         if num > &now {
-            incs = incs + 1;
+            incs += 1;
         }
         now = *num;
     }
@@ -38,11 +56,17 @@ fn count_increase(numbers: &Vec<i32>) -> i32 {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 1692);
+        answers::check(&Day01a);
+    }
+
+    #[test]
+    fn no_numbers() {
+        let e = read_input("").unwrap_err();
+        assert_eq!(e.to_string(), "1:1 expected a number");
+        assert_eq!(solve_numbers(read_input("7").unwrap()), 0);
     }
 }
@@ -1,26 +1,50 @@
-use std::path::Path;
+#![allow(clippy::ptr_arg)]
 
-use crate::aoc::file;
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
 
-pub(crate) fn solve() -> i32 {
-    solve_file(&file::input("input01.txt"))
+pub(crate) struct Day01b;
+
+impl Solution for Day01b {
+    fn day(&self) -> u32 {
+        1
+    }
+
+    fn part(&self) -> char {
+        'b'
+    }
+
+    fn title(&self) -> &'static str {
+        "Sonar Sweep"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_input(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_numbers(solution::unbox(parsed)).into())
+    }
 }
 
-fn solve_file(f: &Path) -> i32 {
-    let numbers = read_input(f);
-    let count = count_increase(&numbers);
-    count
+fn solve_numbers(numbers: Vec<i32>) -> i32 {
+    count_increase(&numbers)
 }
 
-fn read_input(f: &Path) -> Vec<i32> {
-    let lines = file::read_lines(f).unwrap();
+fn read_input(input: &str) -> Result<Vec<i32>, ParseError> {
     let mut numbers = Vec::new();
-    for line in lines {
-        let line = line.unwrap();
-        let number: i32 = line.parse().unwrap();
+    for (no, line) in parse::lines(input) {
+        let number: i32 = parse::number(line, 1).map_err(|e| e.on_line(no))?;
         numbers.push(number);
     }
-    numbers
+    if numbers.len() < 3 {
+        return Err(ParseError::new(
+            numbers.len() + 1,
+            1,
+            "at least three numbers",
+        ));
+    }
+    Ok(numbers)
 }
 
 fn count_increase(numbers: &Vec<i32>) -> i32 {
@@ -29,7 +53,7 @@ fn count_increase(numbers: &Vec<i32>) -> i32 {
     for i in 1..numbers.len() - 2 {
         let num = numbers[i] + numbers[i + 1] + numbers[i + 2];
         if num > now {
-            incs = incs + 1;
+            incs += 1;
         }
         now = num;
     }
@@ -39,11 +63,16 @@ fn count_increase(numbers: &Vec<i32>) -> i32 {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 1724);
+        answers::check(&Day01b);
+    }
+
+    #[test]
+    fn too_few_numbers() {
+        let e = read_input("199\n200").err().unwrap();
+        assert_eq!(e.to_string(), "3:1 expected at least three numbers");
     }
 }
@@ -1,12 +1,31 @@
-use std::path::Path;
-
 use lazy_static::lazy_static;
 use regex::Regex;
 
-use crate::aoc::file;
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
+
+pub(crate) struct Day02a;
+
+impl Solution for Day02a {
+    fn day(&self) -> u32 {
+        2
+    }
+
+    fn part(&self) -> char {
+        'a'
+    }
 
-pub(crate) fn solve() -> i32 {
-    solve_file(&file::input("input02.txt"))
+    fn title(&self) -> &'static str {
+        "Dive!"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_input(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_directions(solution::unbox(parsed)).into())
+    }
 }
 
 enum Direction {
@@ -15,36 +34,36 @@ enum Direction {
     Up(i32),
 }
 
-fn solve_file(f: &Path) -> i32 {
-    let numbers = read_input(f);
+fn solve_directions(numbers: Vec<Direction>) -> i32 {
     let count = follow(&numbers);
     count.0 * count.1
 }
 
-fn read_input(f: &Path) -> Vec<Direction> {
-    let lines = file::read_lines(f).unwrap();
+fn read_input(input: &str) -> Result<Vec<Direction>, ParseError> {
     let mut directions = Vec::new();
-    for line in lines {
-        let line = line.unwrap();
-        let direction: Direction = parse(&line).unwrap();
+    for (no, line) in parse::lines(input) {
+        let direction = parse(line).map_err(|e| e.on_line(no))?;
         directions.push(direction);
     }
-    directions
+    Ok(directions)
 }
 
-fn parse(line: &str) -> Result<Direction, &str> {
+fn parse(line: &str) -> Result<Direction, ParseError> {
     lazy_static! {
         static ref REF: Regex = Regex::new(r"forward (\d+)").unwrap();
         static ref REU: Regex = Regex::new(r"up (\d+)").unwrap();
         static ref RED: Regex = Regex::new(r"down (\d+)").unwrap();
     }
     match REF.captures(line) {
-        Some(m) => Ok(Direction::Forward(m[1].parse().unwrap())),
+        Some(m) => Ok(Direction::Forward(parse::capture(&m, 1)?)),
         None => match REU.captures(line) {
-            Some(m) => Ok(Direction::Up(m[1].parse().unwrap())),
+            Some(m) => Ok(Direction::Up(parse::capture(&m, 1)?)),
             None => match RED.captures(line) {
-                Some(m) => Ok(Direction::Down(m[1].parse().unwrap())),
-                None => Err("syntax"),
+                Some(m) => Ok(Direction::Down(parse::capture(&m, 1)?)),
+                None => Err(ParseError::at(
+                    1,
+                    "'forward', 'up' or 'down' and a distance",
+                )),
             },
         },
     }
@@ -65,11 +84,10 @@ fn follow(numbers: &Vec<Direction>) -> (i32, i32) {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 1868935);
+        answers::check(&Day02a);
     }
 }
@@ -1,26 +1,44 @@
-use std::path::Path;
+#![allow(clippy::needless_range_loop)]
 
-use crate::aoc::file;
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
 
-pub(crate) fn solve() -> i32 {
-    solve_file(&file::input("input03.txt"))
+pub(crate) struct Day03a;
+
+impl Solution for Day03a {
+    fn day(&self) -> u32 {
+        3
+    }
+
+    fn part(&self) -> char {
+        'a'
+    }
+
+    fn title(&self) -> &'static str {
+        "Binary Diagnostic"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_input(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_numbers(solution::unbox(parsed)).into())
+    }
 }
 
-fn solve_file(f: &Path) -> i32 {
-    let numbers = read_input(f);
-    let count = get_values(&numbers);
-    count
+fn solve_numbers(numbers: Vec<i32>) -> i32 {
+    get_values(&numbers)
 }
 
-fn read_input(f: &Path) -> Vec<i32> {
-    let lines = file::read_lines(f).unwrap();
+fn read_input(input: &str) -> Result<Vec<i32>, ParseError> {
     let mut numbers = Vec::new();
-    for line in lines {
-        let line = line.unwrap();
-        let number: i32 = i32::from_str_radix(&line, 2).unwrap();
+    for (no, line) in parse::lines(input) {
+        let number =
+            i32::from_str_radix(line, 2).map_err(|_| ParseError::new(no, 1, "a binary number"))?;
         numbers.push(number);
     }
-    numbers
+    Ok(numbers)
 }
 
 fn get_values(numbers: &Vec<i32>) -> i32 {
@@ -45,9 +63,9 @@ fn get_values(numbers: &Vec<i32>) -> i32 {
         let mask = 1 << bit;
         let f = freq_1bits[bit];
         if f * 2 > nlines as i32 {
-            gamma = gamma | mask;
+            gamma |= mask;
         } else if f * 2 < nlines as i32 {
-            epsilon = epsilon | mask;
+            epsilon |= mask;
         }
     }
     epsilon * gamma
@@ -56,11 +74,10 @@ fn get_values(numbers: &Vec<i32>) -> i32 {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 2498354);
+        answers::check(&Day03a);
     }
 }
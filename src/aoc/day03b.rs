@@ -1,32 +1,69 @@
-use std::path::Path;
+#![allow(clippy::ptr_arg)]
 
-use crate::aoc::file;
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
 
-pub(crate) fn solve() -> i32 {
-    solve_file(&file::input("input03.txt"))
+pub(crate) struct Day03b;
+
+impl Solution for Day03b {
+    fn day(&self) -> u32 {
+        3
+    }
+
+    fn part(&self) -> char {
+        'b'
+    }
+
+    fn title(&self) -> &'static str {
+        "Binary Diagnostic"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_input(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_numbers(solution::unbox(parsed)).into())
+    }
 }
 
-fn solve_file(f: &Path) -> i32 {
-    let numbers = read_input(f);
-    let count = get_values(&numbers);
-    count
+/// The diagnostic report: binary numbers that all have `width` digits.
+struct Report {
+    numbers: Vec<i32>,
+    width: usize,
 }
 
-fn get_values(numbers: &Vec<i32>) -> i32 {
-    let o2 = get_oxygen(numbers, 12);
-    let co2 = get_co2(numbers, 12);
+fn solve_numbers(report: Report) -> i32 {
+    get_values(&report.numbers, report.width)
+}
+
+fn get_values(numbers: &Vec<i32>, nbits: usize) -> i32 {
+    let o2 = get_oxygen(numbers, nbits);
+    let co2 = get_co2(numbers, nbits);
     o2 * co2
 }
 
-fn read_input(f: &Path) -> Vec<i32> {
-    let lines = file::read_lines(f).unwrap();
+fn read_input(input: &str) -> Result<Report, ParseError> {
     let mut numbers = Vec::new();
-    for line in lines {
-        let line = line.unwrap();
-        let number: i32 = i32::from_str_radix(&line, 2).unwrap();
+    let mut width = 0;
+    for (no, line) in parse::lines(input) {
+        let number =
+            i32::from_str_radix(line, 2).map_err(|_| ParseError::new(no, 1, "a binary number"))?;
+        if numbers.is_empty() {
+            width = line.len();
+        } else if line.len() != width {
+            return Err(ParseError::new(
+                no,
+                1,
+                &format!("a binary number of {} digits", width),
+            ));
+        }
         numbers.push(number);
     }
-    numbers
+    if numbers.is_empty() {
+        return Err(ParseError::new(1, 1, "a binary number"));
+    }
+    Ok(Report { numbers, width })
 }
 
 fn get_frequencies(numbers: &Vec<i32>) -> Vec<i32> {
@@ -60,12 +97,15 @@ fn get_oxygen(numbers: &Vec<i32>, nbits: usize) -> i32 {
             let mask = 1 << bit;
             let compare_mask = if most_common == 1 { mask } else { 0 };
             let mut new_rest = vec![];
-            for n in rest {
+            for &n in &rest {
                 if (n & mask) == compare_mask {
                     new_rest.push(n)
                 }
             }
-            rest = new_rest;
+            // all numbers left share the bit, none is less common
+            if !new_rest.is_empty() {
+                rest = new_rest;
+            }
         }
     }
     rest[0]
@@ -85,12 +125,15 @@ fn get_co2(numbers: &Vec<i32>, nbits: usize) -> i32 {
             let mask = 1 << bit;
             let keep_mask = if least_common == 0 { 0 } else { mask };
             let mut new_rest = vec![];
-            for n in rest {
+            for &n in &rest {
                 if (n & mask) == keep_mask {
                     new_rest.push(n)
                 }
             }
-            rest = new_rest;
+            // all numbers left share the bit, none is less common
+            if !new_rest.is_empty() {
+                rest = new_rest;
+            }
         }
     }
     rest[0]
@@ -99,27 +142,44 @@ fn get_co2(numbers: &Vec<i32>, nbits: usize) -> i32 {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
+    use crate::aoc::file;
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 3277956);
+        answers::check(&Day03b);
     }
 
     #[test]
     fn o2() {
-        let numbers = read_input(&file::input("input03-sample.txt"));
-        let o2 = get_oxygen(&numbers, 5);
+        let report = read_input(&file::read_input("input03-sample.txt").unwrap()).unwrap();
+        let o2 = get_oxygen(&report.numbers, report.width);
         println!("result : {}", o2);
         assert_eq!(o2, 23);
     }
 
     #[test]
     fn co2() {
-        let numbers = read_input(&file::input("input03-sample.txt"));
-        let co2 = get_co2(&numbers, 5);
+        let report = read_input(&file::read_input("input03-sample.txt").unwrap()).unwrap();
+        let co2 = get_co2(&report.numbers, report.width);
         println!("result : {}", co2);
         assert_eq!(co2, 10);
     }
+
+    #[test]
+    fn no_numbers() {
+        let e = read_input("").err().unwrap();
+        assert_eq!(e.to_string(), "1:1 expected a binary number");
+    }
+
+    #[test]
+    fn different_widths() {
+        let e = read_input("101\n11").err().unwrap();
+        assert_eq!(e.to_string(), "2:1 expected a binary number of 3 digits");
+    }
+
+    #[test]
+    fn same_numbers() {
+        assert_eq!(solve_numbers(read_input("10\n10").unwrap()), 4);
+    }
 }
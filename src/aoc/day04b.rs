@@ -1,16 +1,34 @@
-use std::path::Path;
-
 use lazy_static::lazy_static;
 use regex::Regex;
 
-use crate::aoc::file;
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
+
+pub(crate) struct Day04b;
+
+impl Solution for Day04b {
+    fn day(&self) -> u32 {
+        4
+    }
+
+    fn part(&self) -> char {
+        'b'
+    }
 
-pub(crate) fn solve() -> i32 {
-    solve_file(&file::input("input04.txt"))
+    fn title(&self) -> &'static str {
+        "Giant Squid"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_input(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_game(solution::unbox(parsed))?.into())
+    }
 }
 
-fn solve_file(f: &Path) -> i32 {
-    let game = read_input(f);
+fn solve_game(game: BingoFile) -> Result<i32, SolveError> {
     let mut winning_number = None;
     let mut winner = None;
     let draws = game.draws;
@@ -31,7 +49,10 @@ fn solve_file(f: &Path) -> i32 {
             break 'outer;
         }
     }
-    winner.unwrap().value() * winning_number.unwrap()
+    match (winner, winning_number) {
+        (Some(board), Some(draw)) => Ok(board.value() * draw),
+        _ => Err(ParseError::new(game.draws_line, 1, "draws that let a board win").into()),
+    }
 }
 
 struct BingoBoard {
@@ -45,7 +66,7 @@ impl BingoBoard {
         for ci in 0..5 {
             for ri in 0..5 {
                 if !self.crosses[ri][ci] {
-                    value = value + self.rows[ri][ci];
+                    value += self.rows[ri][ci];
                 }
             }
         }
@@ -59,17 +80,17 @@ impl BingoBoard {
         for row in &self.crosses {
             let mut row_result = true;
             for val in row {
-                row_result = row_result & val
+                row_result &= val
             }
-            result = result | row_result
+            result |= row_result
         }
         if !result {
             for ci in 0..5 {
                 let mut col_result = true;
                 for ri in 0..5 {
-                    col_result = col_result & self.crosses[ri][ci]
+                    col_result &= self.crosses[ri][ci]
                 }
-                result = result | col_result
+                result |= col_result
             }
         }
         result
@@ -91,49 +112,55 @@ impl BingoBoard {
 struct BingoFile {
     boards: Vec<BingoBoard>,
     draws: Vec<i32>,
+    draws_line: usize,
 }
 
-fn read_input(f: &Path) -> BingoFile {
-    let mut lines: Vec<String> = read_lines(f);
-    lines.retain(|line| line.len() != 0);
-    let draws = read_draws(&lines[0]);
-    lines.remove(0);
+fn read_input(input: &str) -> Result<BingoFile, ParseError> {
+    let end = input.lines().count() + 1;
+    let mut lines: Vec<(usize, &str)> = parse::lines(input).collect();
+    lines.retain(|(_, line)| !line.is_empty());
+    if lines.is_empty() {
+        return Err(ParseError::new(end, 1, "a line of draws"));
+    }
+    let (no, line) = lines.remove(0);
+    let draws = read_draws(line).map_err(|e| e.on_line(no))?;
     let mut boards: Vec<BingoBoard> = Vec::new();
-    while lines.len() != 0 {
-        let board: BingoBoard = read_board(&mut lines);
+    while !lines.is_empty() {
+        let board: BingoBoard = read_board(&mut lines, end)?;
         boards.push(board);
     }
-    BingoFile {
-        draws: draws,
-        boards: boards,
-    }
-}
-
-fn read_lines(p0: &Path) -> Vec<String> {
-    let input = file::read_lines(p0).unwrap();
-    let mut lines = Vec::new();
-    for line in input {
-        lines.push(line.unwrap())
+    if boards.is_empty() {
+        return Err(ParseError::new(end, 1, "a board"));
     }
-    lines
+    Ok(BingoFile {
+        draws,
+        boards,
+        draws_line: no,
+    })
 }
 
-fn read_board(input: &mut Vec<String>) -> BingoBoard {
+fn read_board(input: &mut Vec<(usize, &str)>, end: usize) -> Result<BingoBoard, ParseError> {
     let mut lines: Vec<Vec<i32>> = Vec::new();
     let mut crosses: Vec<Vec<bool>> = Vec::new();
     for _ in 0..5 {
-        let line = input.remove(0);
-        let line = read_numbers(&line);
+        if input.is_empty() {
+            return Err(ParseError::new(end, 1, "5 rows per board"));
+        }
+        let (no, line) = input.remove(0);
+        let line = read_numbers(line).map_err(|e| e.on_line(no))?;
+        if line.len() != 5 {
+            return Err(ParseError::new(no, 1, "5 numbers per row"));
+        }
         lines.push(line);
         crosses.push(vec![false, false, false, false, false])
     }
-    BingoBoard {
+    Ok(BingoBoard {
         rows: lines,
-        crosses: crosses,
-    }
+        crosses,
+    })
 }
 
-fn read_numbers(line: &str) -> Vec<i32> {
+fn read_numbers(line: &str) -> Result<Vec<i32>, ParseError> {
     lazy_static! {
         static ref RE: Regex = Regex::new(
             r"(?x)
@@ -146,12 +173,12 @@ fn read_numbers(line: &str) -> Vec<i32> {
     let mut v: Vec<i32> = Vec::new();
 
     for cap in RE.captures_iter(line) {
-        v.push(cap[1].parse().unwrap());
+        v.push(parse::capture(&cap, 1)?);
     }
-    v
+    Ok(v)
 }
 
-fn read_draws(line: &str) -> Vec<i32> {
+fn read_draws(line: &str) -> Result<Vec<i32>, ParseError> {
     lazy_static! {
         static ref RE: Regex = Regex::new(
             r"(?x)
@@ -164,19 +191,25 @@ fn read_draws(line: &str) -> Vec<i32> {
     let mut v: Vec<i32> = Vec::new();
 
     for cap in RE.captures_iter(line) {
-        v.push(cap[1].parse().unwrap());
+        v.push(parse::capture(&cap, 1)?);
     }
-    v
+    Ok(v)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 15561);
+        answers::check(&Day04b);
+    }
+
+    #[test]
+    fn short_board() {
+        let input = "1,2,3\n\n1 2 3 4 5\n6 7 8 9 10\n11 12 13 14\n";
+        let e = read_input(input).err().unwrap();
+        assert_eq!(e.to_string(), "5:1 expected 5 numbers per row");
     }
 }
@@ -1,21 +1,45 @@
+#![allow(clippy::needless_range_loop, clippy::ptr_arg)]
+
 use std::cmp::{max, min};
-use std::path::Path;
 
 use lazy_static::lazy_static;
-use regex::Regex;
+use regex::{Captures, Regex};
+
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
+
+pub(crate) struct Day05b;
+
+impl Solution for Day05b {
+    fn day(&self) -> u32 {
+        5
+    }
+
+    fn part(&self) -> char {
+        'b'
+    }
+
+    fn title(&self) -> &'static str {
+        "Hydrothermal Venture"
+    }
 
-use crate::aoc::file;
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_input(input)?))
+    }
 
-pub(crate) fn solve() -> i32 {
-    solve_file(&file::input("input05.txt"))
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_lines(solution::unbox(parsed)).into())
+    }
 }
 
-fn solve_file(f: &Path) -> i32 {
-    let numbers = read_input(f);
+fn solve_lines(numbers: Vec<Line>) -> i32 {
     let f = draw_lines(&numbers);
     count_doubles(&f)
 }
 
+/// The vents lie on a field of this many points each way.
+const SIZE: i32 = 1000;
+
 struct Point {
     x: i32,
     y: i32,
@@ -26,47 +50,57 @@ struct Line {
     end: Point,
 }
 
-fn read_input(f: &Path) -> Vec<Line> {
-    let lines = file::read_lines(f).unwrap();
+fn read_input(input: &str) -> Result<Vec<Line>, ParseError> {
     let mut numbers = Vec::new();
-    for l in lines {
-        let l = l.unwrap();
-        let line = read_line(&l);
+    for (no, l) in parse::lines(input) {
+        let line = read_line(l).map_err(|e| e.on_line(no))?;
         numbers.push(line);
     }
-    numbers
+    Ok(numbers)
 }
 
-fn read_line(str: &str) -> Line {
+fn read_line(str: &str) -> Result<Line, ParseError> {
     lazy_static! {
         static ref RE: Regex = Regex::new(
             r"(?x)
-            (\d+)\s*,\s*(\d+)\s*->\s*(\d+)\s*,\s*(\d+)
+            ^\s*(\d+)\s*,\s*(\d+)\s*->\s*(\d+)\s*,\s*(\d+)\s*$
             "
         )
         .unwrap();
     }
     let cap = match RE.captures(str) {
         Some(x) => x,
-        None => panic!("pattern"),
+        None => return Err(ParseError::at(1, "'x1,y1 -> x2,y2'")),
     };
     let start = Point {
-        x: cap[1].parse().unwrap(),
-        y: cap[2].parse().unwrap(),
+        x: coordinate(&cap, 1)?,
+        y: coordinate(&cap, 2)?,
     };
     let end = Point {
-        x: cap[3].parse().unwrap(),
-        y: cap[4].parse().unwrap(),
+        x: coordinate(&cap, 3)?,
+        y: coordinate(&cap, 4)?,
     };
-    Line {
-        start: start,
-        end: end,
+    if start.x != end.x && start.y != end.y && (start.x - end.x).abs() != (start.y - end.y).abs() {
+        return Err(ParseError::at(1, "a horizontal, vertical or diagonal line"));
     }
+    Ok(Line { start, end })
+}
+
+/// The coordinate captured by group `i`, which has to lie on the field.
+fn coordinate(cap: &Captures, i: usize) -> Result<i32, ParseError> {
+    let c = parse::capture(cap, i)?;
+    if c >= SIZE {
+        let column = cap.get(i).unwrap().start() + 1;
+        return Err(ParseError::at(
+            column,
+            &format!("a coordinate below {}", SIZE),
+        ));
+    }
+    Ok(c)
 }
 
 fn draw_lines(lines: &Vec<Line>) -> Vec<Vec<i32>> {
-    let size = 1000;
-    let mut field = empty_field(size);
+    let mut field = empty_field(SIZE);
     for line in lines {
         draw_line(&mut field, line);
     }
@@ -78,13 +112,13 @@ fn draw_line(field: &mut Vec<Vec<i32>>, line: &Line) {
         let ymin = min(line.start.y, line.end.y) as usize;
         let ymax = max(line.start.y, line.end.y) as usize;
         for y in ymin..ymax + 1 {
-            field[y][line.start.x as usize] = field[y][line.start.x as usize] + 1;
+            field[y][line.start.x as usize] += 1;
         }
     } else if line.start.y == line.end.y {
         let xmin = min(line.start.x, line.end.x) as usize;
         let xmax = max(line.start.x, line.end.x) as usize;
         for x in xmin..xmax + 1 {
-            field[line.start.y as usize][x] = field[line.start.y as usize][x] + 1;
+            field[line.start.y as usize][x] += 1;
         }
     } else {
         let (upper, lower) = if line.start.y < line.end.y {
@@ -96,13 +130,13 @@ fn draw_line(field: &mut Vec<Vec<i32>>, line: &Line) {
             let dist = lower.x - upper.x;
             for diff in 0..dist + 1 {
                 let p = &mut field[(upper.y + diff) as usize][(upper.x + diff) as usize];
-                *p = *p + 1
+                *p += 1
             }
         } else {
             let dist = upper.x - lower.x;
             for diff in 0..dist + 1 {
                 let p = &mut field[(upper.y + diff) as usize][(upper.x - diff) as usize];
-                *p = *p + 1
+                *p += 1
             }
         }
     }
@@ -121,7 +155,7 @@ fn count_doubles(field: &Vec<Vec<i32>>) -> i32 {
     for line in field {
         for place in line {
             if *place > 1 {
-                count = count + 1;
+                count += 1;
             }
         }
     }
@@ -131,20 +165,44 @@ fn count_doubles(field: &Vec<Vec<i32>>) -> i32 {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
+    use crate::aoc::file;
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 18605);
+        answers::check(&Day05b);
     }
 
     #[test]
     fn sample() {
-        let f = &file::input("input05-sample.txt");
-        let numbers = read_input(f);
+        let input = file::read_input("input05-sample.txt").unwrap();
+        let numbers = read_input(&input).unwrap();
         let f = draw_lines(&numbers);
         let doubles = count_doubles(&f);
         assert_eq!(doubles, 12);
     }
+
+    #[test]
+    fn outside_the_field() {
+        let e = read_input("0,9 -> 5,9\n0,0 -> 2000000000,0").err().unwrap();
+        assert_eq!(e.to_string(), "2:8 expected a coordinate below 1000");
+    }
+
+    #[test]
+    fn junk_around_line() {
+        let e = read_input("x0,9 -> 5,9").err().unwrap();
+        assert_eq!(e.to_string(), "1:1 expected 'x1,y1 -> x2,y2'");
+        let e = read_input("0,9 -> 5,9,7").err().unwrap();
+        assert_eq!(e.to_string(), "1:1 expected 'x1,y1 -> x2,y2'");
+        assert!(read_input(" 0, 9  ->  5,9 ").is_ok());
+    }
+
+    #[test]
+    fn not_at_45_degrees() {
+        let e = read_input("0,0 -> 3,5").err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "1:1 expected a horizontal, vertical or diagonal line"
+        );
+    }
 }
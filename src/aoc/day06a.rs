@@ -1,16 +1,34 @@
-use std::path::Path;
-
 use lazy_static::lazy_static;
 use regex::Regex;
 
-use crate::aoc::file;
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
+
+pub(crate) struct Day06a;
+
+impl Solution for Day06a {
+    fn day(&self) -> u32 {
+        6
+    }
+
+    fn part(&self) -> char {
+        'a'
+    }
+
+    fn title(&self) -> &'static str {
+        "Lanternfish"
+    }
 
-pub(crate) fn solve() -> u64 {
-    solve_file(&file::input("input06.txt"))
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_input(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_fishes(solution::unbox(parsed)).into())
+    }
 }
 
-fn solve_file(f: &Path) -> u64 {
-    let fishes = read_input(f);
+fn solve_fishes(fishes: Vec<i32>) -> u64 {
     let mut counts = cout_fish_ages(fishes);
     for _ in 0..80 {
         counts = generation(counts)
@@ -21,7 +39,7 @@ fn solve_file(f: &Path) -> u64 {
 fn count_fishes(counts: &Vec<u64>) -> u64 {
     let mut sum = 0;
     for count in counts {
-        sum = sum + count
+        sum += count
     }
     sum
 }
@@ -29,37 +47,35 @@ fn count_fishes(counts: &Vec<u64>) -> u64 {
 fn cout_fish_ages(fishes: Vec<i32>) -> Vec<u64> {
     let mut ages = vec![0; 9];
     for fish in fishes {
-        ages[fish as usize] = ages[fish as usize] + 1
+        ages[fish as usize] += 1
     }
     ages
 }
 
 fn generation(ages: Vec<u64>) -> Vec<u64> {
     let mut newages = vec![0; 9];
-    for age in 1..9 {
-        newages[age - 1] = ages[age];
-    }
+    newages[..8].copy_from_slice(&ages[1..9]);
     newages[8] = ages[0];
-    newages[6] = newages[6] + ages[0];
+    newages[6] += ages[0];
     newages
 }
 
-fn read_input(f: &Path) -> Vec<i32> {
-    let lines: Vec<String> = read_lines(f);
-    let draws = read_number_line(&lines[0]);
-    draws
-}
-
-fn read_lines(p0: &Path) -> Vec<String> {
-    let input = file::read_lines(p0).unwrap();
-    let mut lines = Vec::new();
-    for line in input {
-        lines.push(line.unwrap())
+fn read_input(input: &str) -> Result<Vec<i32>, ParseError> {
+    let line = input.lines().next().unwrap_or("");
+    let fishes = read_number_line(line)?;
+    if fishes.is_empty() {
+        return Err(ParseError::at(1, "a list of fish ages"));
+    }
+    if let Some(age) = fishes.iter().find(|&&age| age > 8) {
+        return Err(ParseError::at(
+            1,
+            &format!("ages from 0 to 8, found {}", age),
+        ));
     }
-    lines
+    Ok(fishes)
 }
 
-fn read_number_line(line: &str) -> Vec<i32> {
+fn read_number_line(line: &str) -> Result<Vec<i32>, ParseError> {
     lazy_static! {
         static ref RE: Regex = Regex::new(
             r"(?x)
@@ -72,26 +88,26 @@ fn read_number_line(line: &str) -> Vec<i32> {
     let mut v: Vec<i32> = Vec::new();
 
     for cap in RE.captures_iter(line) {
-        v.push(cap[1].parse().unwrap());
+        v.push(parse::capture(&cap, 1)?);
     }
-    v
+    Ok(v)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
+    use crate::aoc::file;
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 383160);
+        answers::check(&Day06a);
     }
 
     #[test]
     fn sample() {
-        let f = &file::input("input06-sample.txt");
-        let fishes = read_input(f);
+        let input = file::read_input("input06-sample.txt").unwrap();
+        let fishes = read_input(&input).unwrap();
         let mut counts = cout_fish_ages(fishes);
         for _ in 0..18 {
             counts = generation(counts)
@@ -1,18 +1,38 @@
-use std::path::Path;
+#![allow(clippy::ptr_arg)]
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
-use crate::aoc::file;
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
 
-pub(crate) fn solve() -> i32 {
-    solve_file(&file::input("input07.txt"))
+pub(crate) struct Day07b;
+
+impl Solution for Day07b {
+    fn day(&self) -> u32 {
+        7
+    }
+
+    fn part(&self) -> char {
+        'b'
+    }
+
+    fn title(&self) -> &'static str {
+        "The Treachery of Whales"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_input(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_positions(solution::unbox(parsed)).into())
+    }
 }
 
-fn solve_file(f: &Path) -> i32 {
-    let positions = read_input(f);
-    let min = positions.iter().min().unwrap().clone();
-    let max = positions.iter().max().unwrap().clone();
+fn solve_positions(positions: Vec<i32>) -> i32 {
+    let min = *positions.iter().min().unwrap();
+    let max = *positions.iter().max().unwrap();
     let mut min_fuel = i32::MAX;
     for pos in min..max + 1 {
         let fuel = calculate_fuel(&positions, pos);
@@ -34,22 +54,16 @@ fn get_fuel(pos: i32, p: &i32) -> i32 {
     dist * (dist + 1) / 2
 }
 
-fn read_input(f: &Path) -> Vec<i32> {
-    let lines: Vec<String> = read_lines(f);
-    let draws = read_number_line(&lines[0]);
-    draws
-}
-
-fn read_lines(p0: &Path) -> Vec<String> {
-    let input = file::read_lines(p0).unwrap();
-    let mut lines = Vec::new();
-    for line in input {
-        lines.push(line.unwrap())
+fn read_input(input: &str) -> Result<Vec<i32>, ParseError> {
+    let line = input.lines().next().unwrap_or("");
+    let positions = read_number_line(line)?;
+    if positions.is_empty() {
+        return Err(ParseError::at(1, "a list of positions"));
     }
-    lines
+    Ok(positions)
 }
 
-fn read_number_line(line: &str) -> Vec<i32> {
+fn read_number_line(line: &str) -> Result<Vec<i32>, ParseError> {
     lazy_static! {
         static ref RE: Regex = Regex::new(
             r"(?x)
@@ -62,20 +76,19 @@ fn read_number_line(line: &str) -> Vec<i32> {
     let mut v: Vec<i32> = Vec::new();
 
     for cap in RE.captures_iter(line) {
-        v.push(cap[1].parse().unwrap());
+        v.push(parse::capture(&cap, 1)?);
     }
-    v
+    Ok(v)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 93397632);
+        answers::check(&Day07b);
     }
 
     /*    #[test]
@@ -1,21 +1,38 @@
-use std::path::Path;
+#![allow(clippy::ptr_arg)]
 
-use crate::aoc::file;
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
 
-pub(crate) fn solve() -> i32 {
-    solve_file(&file::input("input08.txt"))
+pub(crate) struct Day08a;
+
+impl Solution for Day08a {
+    fn day(&self) -> u32 {
+        8
+    }
+
+    fn part(&self) -> char {
+        'a'
+    }
+
+    fn title(&self) -> &'static str {
+        "Seven Segment Search"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_file(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_patterns(solution::unbox(parsed)).into())
+    }
 }
 
-fn solve_file(f: &Path) -> i32 {
-    let pats = read_file(f);
+fn solve_patterns(pats: Vec<Patterns>) -> i32 {
     count_simple(&pats)
 }
 
 fn count_simple(pat: &Vec<Patterns>) -> i32 {
-    pat.iter()
-        .map(|p| &p.output)
-        .map(|o| count_simple_str(o))
-        .sum()
+    pat.iter().map(|p| &p.output).map(count_simple_str).sum()
 }
 
 fn count_simple_str(p0: &Vec<String>) -> i32 {
@@ -24,43 +41,54 @@ fn count_simple_str(p0: &Vec<String>) -> i32 {
         .count() as i32
 }
 
+#[allow(dead_code)]
 struct Patterns {
     input: Vec<String>,
     output: Vec<String>,
 }
 
-fn read_file(p0: &Path) -> Vec<Patterns> {
-    let input = file::read_lines(p0).unwrap();
-    input.map(|l| parse_line(&l.expect("fail"))).collect()
+fn read_file(input: &str) -> Result<Vec<Patterns>, ParseError> {
+    parse::lines(input)
+        .map(|(no, line)| parse_line(line).map_err(|e| e.on_line(no)))
+        .collect()
 }
 
-fn parse_line(line: &str) -> Patterns {
+fn parse_line(line: &str) -> Result<Patterns, ParseError> {
     let q: Vec<&str> = line.split(" | ").collect();
-    let inp = q[0].split(" ").map(|r| String::from(r)).collect();
-    let outp = q[1].split(" ").map(|r| String::from(r)).collect();
-    Patterns {
+    if q.len() != 2 {
+        return Err(ParseError::at(1, "ten patterns, ' | ' and four digits"));
+    }
+    let inp = q[0].split(" ").map(String::from).collect();
+    let outp = q[1].split(" ").map(String::from).collect();
+    Ok(Patterns {
         input: inp,
         output: outp,
-    }
+    })
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
 
     #[test]
     fn test_parse_line() {
         let result = parse_line(
             "edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc",
-        );
+        )
+        .unwrap();
         assert_eq!(result.input.len(), 10);
         assert_eq!(result.output.len(), 4);
     }
 
+    #[test]
+    fn missing_separator() {
+        let e = read_file("ab cd\nab | cd").err().unwrap();
+        assert_eq!(e.line, 1);
+    }
+
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 521);
+        answers::check(&Day08a);
     }
 }
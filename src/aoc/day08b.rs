@@ -1,17 +1,38 @@
+#![allow(clippy::ptr_arg)]
+
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
 
 use itertools::Itertools;
 
-use crate::aoc::file;
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
+
+pub(crate) struct Day08b;
+
+impl Solution for Day08b {
+    fn day(&self) -> u32 {
+        8
+    }
 
-pub(crate) fn solve() -> i32 {
-    solve_file(&file::input("input08.txt"))
+    fn part(&self) -> char {
+        'b'
+    }
+
+    fn title(&self) -> &'static str {
+        "Seven Segment Search"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_file(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_patterns(solution::unbox(parsed)).into())
+    }
 }
 
-fn solve_file(f: &Path) -> i32 {
-    let pats = read_file(f);
-    pats.iter().map(|p| decode(p)).sum()
+fn solve_patterns(pats: Vec<Patterns>) -> i32 {
+    pats.iter().map(decode).sum()
 }
 
 fn decode(p: &Patterns) -> i32 {
@@ -139,35 +160,41 @@ struct Patterns {
     output: Vec<String>,
 }
 
-fn read_file(p0: &Path) -> Vec<Patterns> {
-    let input = file::read_lines(p0).unwrap();
-    input.map(|l| parse_line(&l.expect("fail"))).collect()
+fn read_file(input: &str) -> Result<Vec<Patterns>, ParseError> {
+    parse::lines(input)
+        .map(|(no, line)| parse_line(line).map_err(|e| e.on_line(no)))
+        .collect()
 }
 
-fn parse_line(line: &str) -> Patterns {
+fn parse_line(line: &str) -> Result<Patterns, ParseError> {
     fn bla(s: &str) -> Vec<String> {
         s.split(" ")
             .map(|r| r.chars().sorted().collect::<String>())
             .collect()
     }
     let q: Vec<&str> = line.split(" | ").collect();
+    if q.len() != 2 {
+        return Err(ParseError::at(1, "ten patterns, ' | ' and four digits"));
+    }
     let inp = bla(q[0]);
     let outp = bla(q[1]);
-    Patterns {
+    Ok(Patterns {
         input: inp,
         output: outp,
-    }
+    })
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
 
     #[test]
     fn test_parse_line() {
         let result = parse_line(
             "edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc",
-        );
+        )
+        .unwrap();
         assert_eq!(result.input.len(), 10);
         assert_eq!(result.output.len(), 4);
     }
@@ -176,7 +203,8 @@ mod test {
     fn test_identify() {
         let ps = parse_line(
             "edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc",
-        );
+        )
+        .unwrap();
         let m = identify(&ps.input);
         assert_eq!(m[&0], "abcdfg");
         assert_eq!(m[&1], "cg");
@@ -194,14 +222,13 @@ mod test {
     fn test_decode() {
         let result = decode(&parse_line(
             "edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc",
-        ));
+        )
+        .unwrap());
         assert_eq!(result, 9781);
     }
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 1016804);
+        answers::check(&Day08b);
     }
 }
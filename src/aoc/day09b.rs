@@ -1,14 +1,33 @@
-use std::path::Path;
+use crate::aoc::grid::Grid;
+use crate::aoc::parse::ParseError;
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
 
-use crate::aoc::file;
+pub(crate) struct Day09b;
 
-pub(crate) fn solve() -> u32 {
-    solve_file(&file::input("input09.txt"))
+impl Solution for Day09b {
+    fn day(&self) -> u32 {
+        9
+    }
+
+    fn part(&self) -> char {
+        'b'
+    }
+
+    fn title(&self) -> &'static str {
+        "Smoke Basin"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_file(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_heights(solution::unbox(parsed)).into())
+    }
 }
 
-fn solve_file(f: &Path) -> u32 {
+fn solve_heights(mut heights: Grid<i8>) -> u32 {
     let mut sizes: Vec<u32> = vec![];
-    let mut heights = read_file(f);
     loop {
         match get_low_point(&heights) {
             None => {
@@ -20,99 +39,73 @@ fn solve_file(f: &Path) -> u32 {
         };
     }
     sizes.sort_by(|a, b| b.cmp(a));
-    sizes[0] * sizes[1] * sizes[2]
+    sizes.iter().take(3).product()
 }
 
-fn raise_basin(a: &mut Vec<Vec<i8>>, x: usize, y: usize) -> u32 {
+fn raise_basin(a: &mut Grid<i8>, x: usize, y: usize) -> u32 {
     if !is_deep_or_flat(a, x, y) {
         0
     } else {
-        a[y][x] = 9;
-        1 + if x > 0 { raise_basin(a, x - 1, y) } else { 0 }
-            + if y > 0 { raise_basin(a, x, y - 1) } else { 0 }
-            + if x < a[y].len() - 1 {
-                raise_basin(a, x + 1, y)
-            } else {
-                0
-            }
-            + if y < a.len() - 1 {
-                raise_basin(a, x, y + 1)
-            } else {
-                0
-            }
-    }
-}
-
-fn get_low_point(a: &Vec<Vec<i8>>) -> Option<(usize, usize)> {
-    let mut ret: Option<(usize, usize)> = None;
-    for y in 0..a.len() {
-        for x in 0..a[y].len() {
-            if is_deep(a, x, y) {
-                ret = Some((x, y));
-                break;
-            }
-        }
+        a[(x, y)] = 9;
+        let neighbours: Vec<(usize, usize)> = a.neighbours4(x, y).collect();
+        1 + neighbours
+            .into_iter()
+            .map(|(nx, ny)| raise_basin(a, nx, ny))
+            .sum::<u32>()
     }
-    ret
 }
 
-fn is_deep(a: &Vec<Vec<i8>>, x: usize, y: usize) -> bool {
-    (if x > 0 { a[y][x] < a[y][x - 1] } else { true })
-        && (if y > 0 { a[y][x] < a[y - 1][x] } else { true })
-        && (if x < a[y].len() - 1 {
-            a[y][x] < a[y][x + 1]
-        } else {
-            true
-        })
-        && (if y < a.len() - 1 {
-            a[y][x] < a[y + 1][x]
-        } else {
-            true
-        })
+fn get_low_point(a: &Grid<i8>) -> Option<(usize, usize)> {
+    a.positions().find(|&(x, y)| is_deep(a, x, y))
 }
 
-fn is_deep_or_flat(a: &Vec<Vec<i8>>, x: usize, y: usize) -> bool {
-    a[y][x] < 9
-        && (if x > 0 { a[y][x] <= a[y][x - 1] } else { true })
-        && (if y > 0 { a[y][x] <= a[y - 1][x] } else { true })
-        && (if x < a[y].len() - 1 {
-            a[y][x] <= a[y][x + 1]
-        } else {
-            true
-        })
-        && (if y < a.len() - 1 {
-            a[y][x] <= a[y + 1][x]
-        } else {
-            true
-        })
+fn is_deep(a: &Grid<i8>, x: usize, y: usize) -> bool {
+    a.neighbours4(x, y).all(|n| a[(x, y)] < a[n])
 }
 
-fn read_file(p0: &Path) -> Vec<Vec<i8>> {
-    let input = file::read_lines(p0).unwrap();
-    input.map(|l| parse_line(&l.expect("fail"))).collect()
+fn is_deep_or_flat(a: &Grid<i8>, x: usize, y: usize) -> bool {
+    a[(x, y)] < 9 && a.neighbours4(x, y).all(|n| a[(x, y)] <= a[n])
 }
 
-fn parse_line(line: &str) -> Vec<i8> {
-    line.chars()
-        .map(|c| c.to_digit(10).unwrap() as i8)
-        .collect()
+fn read_file(input: &str) -> Result<Grid<i8>, ParseError> {
+    let heights = Grid::parse_digits(input)?;
+    if heights.width() == 0 || heights.height() == 0 {
+        return Err(ParseError::at(1, "a height map"));
+    }
+    Ok(heights)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
+    use crate::aoc::file;
+
+    fn solve_input(input: &str) -> Result<u32, SolveError> {
+        Ok(solve_heights(read_file(input)?))
+    }
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 1056330); // 282880 too low
+        answers::check(&Day09b);
     }
 
     #[test]
     fn sample() {
-        let result = solve_file(&file::input("input09-sample.txt"));
+        let result = solve_input(&file::read_input("input09-sample.txt").unwrap()).unwrap();
         println!("result : {}", result);
         assert_eq!(result, 1134);
     }
+
+    #[test]
+    fn bad_digit() {
+        let e = read_file("2199\n39x7").err().unwrap();
+        assert_eq!(e.to_string(), "2:3 expected a digit, found 'x'");
+    }
+
+    #[test]
+    fn empty_map() {
+        let e = read_file("").err().unwrap();
+        assert_eq!(e.to_string(), "1:1 expected a height map");
+    }
 }
@@ -1,25 +1,62 @@
-use std::path::Path;
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
 
-use crate::aoc::file;
+pub(crate) struct Day10a;
 
-pub(crate) fn solve() -> i32 {
-    solve_file(&file::input("input10.txt"))
+impl Solution for Day10a {
+    fn day(&self) -> u32 {
+        10
+    }
+
+    fn part(&self) -> char {
+        'a'
+    }
+
+    fn title(&self) -> &'static str {
+        "Syntax Scoring"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_lines(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_lines(solution::unbox(parsed)).into())
+    }
 }
 
-fn solve_file(f: &Path) -> i32 {
+fn solve_lines(lines: Vec<String>) -> i32 {
     let mut sum = 0;
-    let heights = file::read_lines(f).unwrap();
-    for h in heights {
-        let r = parse(&h.unwrap());
-        sum = sum
-            + match r {
-                Syntax::Corruped(s) => s,
-                _ => 0,
-            }
+    for h in &lines {
+        let r = parse(h);
+        sum += match r {
+            Syntax::Corruped(s) => s,
+            _ => 0,
+        }
     }
     sum
 }
 
+fn read_lines(input: &str) -> Result<Vec<String>, ParseError> {
+    parse::lines(input)
+        .map(|(no, line)| {
+            check_brackets(line).map_err(|e| e.on_line(no))?;
+            Ok(String::from(line))
+        })
+        .collect()
+}
+
+fn check_brackets(line: &str) -> Result<(), ParseError> {
+    match line
+        .chars()
+        .enumerate()
+        .find(|(_, c)| !"()[]{}<>".contains(*c))
+    {
+        Some((i, c)) => Err(ParseError::at(i + 1, &format!("a bracket, found '{}'", c))),
+        None => Ok(()),
+    }
+}
+
 enum Syntax {
     Ok,
     Incomplete,
@@ -81,10 +118,7 @@ fn parse_multi(line: &mut Vec<char>) -> Syntax {
 }
 
 fn is_closing(last: &char) -> bool {
-    match last {
-        ')' | ']' | '}' | '>' => true,
-        _ => false,
-    }
+    matches!(last, ')' | ']' | '}' | '>')
 }
 
 fn get_score(last: &char) -> i32 {
@@ -110,16 +144,20 @@ fn is_matching(first: &char, last: &char) -> bool {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
+    use crate::aoc::file;
+
+    fn solve_input(input: &str) -> Result<i32, SolveError> {
+        Ok(solve_lines(read_lines(input)?))
+    }
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 388713);
+        answers::check(&Day10a);
     }
     #[test]
     fn sample() {
-        let result = solve_file(&file::input("input10-sample.txt"));
+        let result = solve_input(&file::read_input("input10-sample.txt").unwrap()).unwrap();
         println!("result : {}", result);
         assert_eq!(result, 26397);
     }
@@ -149,4 +187,10 @@ mod test {
         let result = parse(")");
         assert!(matches!(result, Syntax::Corruped(_)));
     }
+
+    #[test]
+    fn not_a_bracket() {
+        let e = solve_input("()\n(a)").unwrap_err();
+        assert_eq!(e.to_string(), "2:2 expected a bracket, found 'a'");
+    }
 }
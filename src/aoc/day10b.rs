@@ -1,26 +1,66 @@
-use std::path::Path;
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
 
-use crate::aoc::file;
+pub(crate) struct Day10b;
 
-pub(crate) fn solve() -> u64 {
-    solve_file(&file::input("input10.txt"))
+impl Solution for Day10b {
+    fn day(&self) -> u32 {
+        10
+    }
+
+    fn part(&self) -> char {
+        'b'
+    }
+
+    fn title(&self) -> &'static str {
+        "Syntax Scoring"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_lines(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_lines(solution::unbox(parsed))?.into())
+    }
 }
 
-fn solve_file(f: &Path) -> u64 {
-    let lines = file::read_lines(f).unwrap();
+fn solve_lines(lines: Vec<String>) -> Result<u64, SolveError> {
     let mut results: Vec<u64> = Vec::new();
-    for line in lines {
-        let r = parse(&line.unwrap());
-        match r {
-            Syntax::Incomplete(score) => results.push(score),
-            _ => {}
+    for line in &lines {
+        let r = parse(line);
+        if let Syntax::Incomplete(score) = r {
+            results.push(score)
         }
     }
+    if results.is_empty() {
+        return Err(ParseError::new(lines.len() + 1, 1, "an incomplete line").into());
+    }
     results.sort();
-    let result: u64 = *results.get(results.len() / 2).unwrap();
-    result
+    Ok(results[results.len() / 2])
+}
+
+fn read_lines(input: &str) -> Result<Vec<String>, ParseError> {
+    parse::lines(input)
+        .map(|(no, line)| {
+            check_brackets(line).map_err(|e| e.on_line(no))?;
+            Ok(String::from(line))
+        })
+        .collect()
+}
+
+fn check_brackets(line: &str) -> Result<(), ParseError> {
+    match line
+        .chars()
+        .enumerate()
+        .find(|(_, c)| !"()[]{}<>".contains(*c))
+    {
+        Some((i, c)) => Err(ParseError::at(i + 1, &format!("a bracket, found '{}'", c))),
+        None => Ok(()),
+    }
 }
 
+#[allow(dead_code)]
 enum Syntax {
     Ok,
     Incomplete(u64),
@@ -86,10 +126,7 @@ fn parse_multi(line: &mut Vec<char>) -> Syntax {
 }
 
 fn is_closing(last: &char) -> bool {
-    match last {
-        ')' | ']' | '}' | '>' => true,
-        _ => false,
-    }
+    matches!(last, ')' | ']' | '}' | '>')
 }
 
 fn get_score(last: &char) -> i32 {
@@ -125,16 +162,20 @@ fn is_matching(first: &char, last: &char) -> bool {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
+    use crate::aoc::file;
+
+    fn solve_input(input: &str) -> Result<u64, SolveError> {
+        solve_lines(read_lines(input)?)
+    }
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 3539961434);
+        answers::check(&Day10b);
     }
     #[test]
     fn sample() {
-        let result = solve_file(&file::input("input10-sample.txt"));
+        let result = solve_input(&file::read_input("input10-sample.txt").unwrap()).unwrap();
         println!("result : {}", result);
         assert_eq!(result, 288957);
     }
@@ -164,4 +205,9 @@ mod test {
         let result = parse(")");
         assert!(matches!(result, Syntax::Corruped(_)));
     }
+    #[test]
+    fn nothing_incomplete() {
+        let e = solve_input("()\n[)").err().unwrap();
+        assert_eq!(e.to_string(), "3:1 expected an incomplete line");
+    }
 }
@@ -1,55 +1,68 @@
-use std::cmp::{max, min};
-use std::path::Path;
+use crate::aoc::grid::Grid;
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
 
-use crate::aoc::file;
+pub(crate) struct Day11b;
 
-pub(crate) fn solve() -> u32 {
-    solve_file(&file::input("input11.txt"))
+impl Solution for Day11b {
+    fn day(&self) -> u32 {
+        11
+    }
+
+    fn part(&self) -> char {
+        'b'
+    }
+
+    fn title(&self) -> &'static str {
+        "Dumbo Octopus"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_field(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_field(solution::unbox(parsed)).into())
+    }
 }
 
-fn solve_file(f: &Path) -> u32 {
-    let mut field = read_field(f);
+fn solve_field(mut field: Grid<Field>) -> u32 {
+    let octopuses = (field.width() * field.height()) as u32;
     let mut counter = 0u32;
     loop {
         counter += 1;
         let flashes = iterate_field(&mut field);
-        if flashes == 100 {
+        if flashes == octopuses {
             break;
         }
     }
     counter
 }
 
-fn iterate_field(field: &mut Vec<Vec<Field>>) -> u32 {
+fn iterate_field(field: &mut Grid<Field>) -> u32 {
     increment_field(field);
     let counter = flash_field(field);
     reset_flashes(field);
     counter
 }
 
-fn increment_field(field: &mut Vec<Vec<Field>>) {
-    for line in field {
-        for f in line {
-            match f {
-                Field::Off(v) => *f = Field::Off(*v + 1),
-                _ => {}
-            }
+fn increment_field(field: &mut Grid<Field>) {
+    for f in field.iter_mut() {
+        if let Field::Off(v) = f {
+            *f = Field::Off(*v + 1)
         }
     }
 }
 
-fn reset_flashes(field: &mut Vec<Vec<Field>>) {
-    for line in field {
-        for f in line {
-            match *f {
-                Field::Flash => *f = Field::Off(0),
-                _ => {}
-            }
+fn reset_flashes(field: &mut Grid<Field>) {
+    for f in field.iter_mut() {
+        if let Field::Flash = *f {
+            *f = Field::Off(0)
         }
     }
 }
 
-fn flash_field(field: &mut Vec<Vec<Field>>) -> u32 {
+fn flash_field(field: &mut Grid<Field>) -> u32 {
     let mut counter = 0u32;
     loop {
         let c = flash_once(field);
@@ -61,51 +74,39 @@ fn flash_field(field: &mut Vec<Vec<Field>>) -> u32 {
     counter
 }
 
-fn flash_once(field: &mut Vec<Vec<Field>>) -> u32 {
+fn flash_once(field: &mut Grid<Field>) -> u32 {
     let mut counter = 0u32;
 
-    for y in 0..field.len() {
-        let line_len = field.get(y).unwrap().len();
-        for x in 0..line_len {
-            let f = field[y][x].clone();
-            match f {
-                Field::Off(v) => {
-                    if v > 9 {
-                        flash(field, y, x);
-                        counter += 1;
-                    }
-                }
-                _ => {}
+    let positions: Vec<(usize, usize)> = field.positions().collect();
+    for (x, y) in positions {
+        if let Field::Off(v) = field[(x, y)] {
+            if v > 9 {
+                flash(field, x, y);
+                counter += 1;
             }
         }
     }
     counter
 }
 
-fn flash(field: &mut Vec<Vec<Field>>, y: usize, x: usize) {
-    for y0 in max(0, y as i32 - 1) as usize..min(10, y + 2) {
-        for x0 in max(0, x as i32 - 1) as usize..min(10, x + 2) {
-            match field[y0][x0] {
-                Field::Off(v) => {
-                    field[y0][x0] = Field::Off(v + 1);
-                }
-                Field::Flash => {}
-            }
+fn flash(field: &mut Grid<Field>, x: usize, y: usize) {
+    let neighbours: Vec<(usize, usize)> = field.neighbours8(x, y).collect();
+    for n in neighbours {
+        if let Field::Off(v) = field[n] {
+            field[n] = Field::Off(v + 1);
         }
     }
-    field[y][x] = Field::Flash
+    field[(x, y)] = Field::Flash
 }
 
-fn read_field(f: &Path) -> Vec<Vec<Field>> {
-    let lines = file::read_lines(f).unwrap();
-    lines
-        .map(|l| {
-            l.unwrap()
-                .chars()
-                .map(|c| Field::Off(c.to_digit(10).unwrap()))
-                .collect()
-        })
-        .collect()
+fn read_field(input: &str) -> Result<Grid<Field>, ParseError> {
+    let field = Grid::parse(input, |c, column| {
+        parse::digit(c, 10, column).map(Field::Off)
+    })?;
+    if field.width() == 0 || field.height() == 0 {
+        return Err(ParseError::at(1, "a grid of energy levels"));
+    }
+    Ok(field)
 }
 
 #[derive(Clone)]
@@ -117,11 +118,22 @@ enum Field {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 222);
+        answers::check(&Day11b);
+    }
+
+    #[test]
+    fn other_sizes() {
+        assert_eq!(solve_field(read_field("9").unwrap()), 1);
+        assert_eq!(solve_field(read_field("99\n98").unwrap()), 1);
+    }
+
+    #[test]
+    fn empty_field() {
+        let e = read_field("").err().unwrap();
+        assert_eq!(e.to_string(), "1:1 expected a grid of energy levels");
     }
 }
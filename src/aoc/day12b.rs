@@ -1,17 +1,36 @@
 use std::collections::HashSet;
-use std::path::Path;
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
-use crate::aoc::file;
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
 
-pub(crate) fn solve() -> u32 {
-    solve_file(&file::input("input12.txt"))
+pub(crate) struct Day12b;
+
+impl Solution for Day12b {
+    fn day(&self) -> u32 {
+        12
+    }
+
+    fn part(&self) -> char {
+        'b'
+    }
+
+    fn title(&self) -> &'static str {
+        "Passage Pathing"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_input(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_connections(solution::unbox(parsed)).into())
+    }
 }
 
-fn solve_file(f: &Path) -> u32 {
-    let conns = read_input(f);
+fn solve_connections(conns: Network) -> u32 {
     let empty = &HashSet::new();
     ways_from(&conns, "start", "", empty, false).len() as u32
 }
@@ -37,9 +56,9 @@ fn ways_from(
         let x = network.destinations_from(here);
         let mut rc: HashSet<String> = HashSet::new();
         for now in x.difference(&nvisited) {
-            rc.extend(ways_from(network, now, &path, &nvisited, twice).into_iter());
+            rc.extend(ways_from(network, now, &path, &nvisited, twice));
             if !twice && here != "start" && is_small(here) {
-                rc.extend(ways_from(network, now, &path, visited, true).into_iter());
+                rc.extend(ways_from(network, now, &path, visited, true));
             }
         }
         rc
@@ -79,22 +98,20 @@ impl Network {
     }
 }
 
-fn read_input(f: &Path) -> Network {
-    let lines = file::read_lines(f).unwrap();
+fn read_input(input: &str) -> Result<Network, ParseError> {
     let mut directions = Vec::new();
-    for line in lines {
-        let line = line.unwrap();
-        let direction: Connection = parse(&line).unwrap();
+    for (no, line) in parse::lines(input) {
+        let direction: Connection = parse(line).map_err(|e| e.on_line(no))?;
         directions.push(direction);
     }
-    Network {
+    Ok(Network {
         connections: directions,
-    }
+    })
 }
 
-fn parse(line: &str) -> Result<Connection, &str> {
+fn parse(line: &str) -> Result<Connection, ParseError> {
     lazy_static! {
-        static ref RE: Regex = Regex::new(r"(\w+)-(\w+)").unwrap();
+        static ref RE: Regex = Regex::new(r"^(\w+)-(\w+)$").unwrap();
     }
     match RE.captures(line) {
         Some(m) => {
@@ -103,18 +120,17 @@ fn parse(line: &str) -> Result<Connection, &str> {
             let c = Connection { from: s1, to: s2 };
             Ok(c)
         }
-        None => Err("fail"),
+        None => Err(ParseError::at(1, "a connection like 'start-A'")),
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 118803);
+        answers::check(&Day12b);
     }
 }
@@ -1,17 +1,36 @@
 use std::collections::HashSet;
-use std::path::Path;
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
-use crate::aoc::file;
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
 
-pub(crate) fn solve() -> usize {
-    solve_file(&file::input("input13.txt"))
+pub(crate) struct Day13a;
+
+impl Solution for Day13a {
+    fn day(&self) -> u32 {
+        13
+    }
+
+    fn part(&self) -> char {
+        'a'
+    }
+
+    fn title(&self) -> &'static str {
+        "Transparent Origami"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_input(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_sheet(solution::unbox(parsed)).into())
+    }
 }
 
-fn solve_file(f: &Path) -> usize {
-    let conns = read_input(f);
+fn solve_sheet(conns: Sheet) -> usize {
     let result = fold(&conns.point, &conns.instr[0]);
     result.len()
 }
@@ -73,60 +92,71 @@ struct Sheet {
     instr: Vec<Instruction>,
 }
 
-fn read_input(f: &Path) -> Sheet {
+fn read_input(input: &str) -> Result<Sheet, ParseError> {
     lazy_static! {
-        static ref RE: Regex = Regex::new(r"(\w+),(\w+)").unwrap();
-        static ref REFY: Regex = Regex::new(r"fold along y=(\w+)").unwrap();
-        static ref REFX: Regex = Regex::new(r"fold along x=(\w+)").unwrap();
+        static ref RE: Regex = Regex::new(r"^(\d+),(\d+)$").unwrap();
+        static ref REFY: Regex = Regex::new(r"^fold along y=(\d+)$").unwrap();
+        static ref REFX: Regex = Regex::new(r"^fold along x=(\d+)$").unwrap();
     }
-    let lines = file::read_lines(f).unwrap();
     let mut points = HashSet::new();
     let mut instr = Vec::new();
-    for line in lines {
-        let line = line.unwrap();
+    for (no, line) in parse::lines(input) {
         if !line.is_empty() {
-            match RE.captures(&line) {
+            match RE.captures(line) {
                 Some(m) => {
-                    let s1 = String::from(&m[1]).parse().unwrap();
-                    let s2 = String::from(&m[2]).parse().unwrap();
+                    let s1 = parse::capture(&m, 1).map_err(|e| e.on_line(no))?;
+                    let s2 = parse::capture(&m, 2).map_err(|e| e.on_line(no))?;
                     let c = Point { x: s1, y: s2 };
                     points.insert(c);
                 }
-                None => match REFX.captures(&line) {
+                None => match REFX.captures(line) {
                     Some(m) => {
-                        let s1 = String::from(&m[1]).parse().unwrap();
+                        let s1 = parse::capture(&m, 1).map_err(|e| e.on_line(no))?;
                         let c = Instruction::OnX(s1);
                         instr.push(c);
                     }
-                    None => match REFY.captures(&line) {
+                    None => match REFY.captures(line) {
                         Some(m) => {
-                            let s1 = String::from(&m[1]).parse().unwrap();
+                            let s1 = parse::capture(&m, 1).map_err(|e| e.on_line(no))?;
                             let c = Instruction::OnY(s1);
                             instr.push(c);
                         }
                         None => {
-                            panic!("fail");
+                            return Err(ParseError::new(
+                                no,
+                                1,
+                                "a point 'x,y' or 'fold along x=' / 'fold along y='",
+                            ));
                         }
                     },
                 },
             }
         }
     }
+    if instr.is_empty() {
+        let end = input.lines().count() + 1;
+        return Err(ParseError::new(end, 1, "a fold instruction"));
+    }
 
-    Sheet {
+    Ok(Sheet {
         point: points,
-        instr: instr,
-    }
+        instr,
+    })
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 4413);
+        answers::check(&Day13a);
+    }
+
+    #[test]
+    fn no_folds() {
+        let e = read_input("6,10\n0,14\n").err().unwrap();
+        assert_eq!(e.to_string(), "3:1 expected a fold instruction");
     }
 }
@@ -1,39 +1,73 @@
 use std::collections::HashSet;
-use std::path::Path;
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
-use crate::aoc::file;
+use crate::aoc::grid::Grid;
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
 
-pub(crate) fn solve() -> usize {
-    solve_file(&file::input("input13.txt"))
+pub(crate) struct Day13b;
+
+impl Solution for Day13b {
+    fn day(&self) -> u32 {
+        13
+    }
+
+    fn part(&self) -> char {
+        'b'
+    }
+
+    fn title(&self) -> &'static str {
+        "Transparent Origami"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_input(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_sheet(solution::unbox(parsed)).into())
+    }
 }
 
-fn solve_file(f: &Path) -> usize {
-    let conns = read_input(f);
+fn solve_sheet(conns: Sheet) -> String {
     let mut points = conns.point;
     for i in conns.instr {
         points = fold(&points, &i);
     }
-    display(&points);
-    0
+    display(&points)
 }
 
-fn display(points: &HashSet<Point>) {
-    let w = points.iter().map(|p| p.x).max().unwrap() + 1;
-    let h = points.iter().map(|p| p.y).max().unwrap() + 1;
-    let mut d = Vec::new();
-    for _y in 0..h {
-        d.push(vec![' '; w as usize])
-    }
+fn display(points: &HashSet<Point>) -> String {
+    sheet(points)
+        .rows()
+        .map(|row| row.iter().map(|&p| if p { '#' } else { ' ' }).collect())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
 
+/// The dots on the sheet, as far as it reaches.
+fn sheet(points: &HashSet<Point>) -> Grid<bool> {
+    let w = points.iter().map(|p| p.x + 1).max().unwrap_or(0);
+    let h = points.iter().map(|p| p.y + 1).max().unwrap_or(0);
+    let mut g = Grid::new(w as usize, h as usize, false);
     for p in points {
-        d[p.y as usize][p.x as usize] = '#'
+        g[(p.x as usize, p.y as usize)] = true;
     }
-    for l in d {
-        println!("{}", String::from_iter(l))
+    g
+}
+
+/// The sheet before folding and after every fold.
+pub(crate) fn frames(input: &str) -> Result<Vec<Grid<bool>>, ParseError> {
+    let conns = read_input(input)?;
+    let mut points = conns.point;
+    let mut frames = vec![sheet(&points)];
+    for i in conns.instr {
+        points = fold(&points, &i);
+        frames.push(sheet(&points));
     }
+    Ok(frames)
 }
 
 fn fold(points: &HashSet<Point>, instr: &Instruction) -> HashSet<Point> {
@@ -93,60 +127,114 @@ struct Sheet {
     instr: Vec<Instruction>,
 }
 
-fn read_input(f: &Path) -> Sheet {
+fn read_input(input: &str) -> Result<Sheet, ParseError> {
     lazy_static! {
-        static ref RE: Regex = Regex::new(r"(\w+),(\w+)").unwrap();
-        static ref REFY: Regex = Regex::new(r"fold along y=(\w+)").unwrap();
-        static ref REFX: Regex = Regex::new(r"fold along x=(\w+)").unwrap();
+        static ref RE: Regex = Regex::new(r"^(\d+),(\d+)$").unwrap();
+        static ref REFY: Regex = Regex::new(r"^fold along y=(\d+)$").unwrap();
+        static ref REFX: Regex = Regex::new(r"^fold along x=(\d+)$").unwrap();
     }
-    let lines = file::read_lines(f).unwrap();
     let mut points = HashSet::new();
     let mut instr = Vec::new();
-    for line in lines {
-        let line = line.unwrap();
+    let mut fold_lines = Vec::new();
+    for (no, line) in parse::lines(input) {
         if !line.is_empty() {
-            match RE.captures(&line) {
+            match RE.captures(line) {
                 Some(m) => {
-                    let s1 = String::from(&m[1]).parse().unwrap();
-                    let s2 = String::from(&m[2]).parse().unwrap();
+                    let s1 = parse::capture(&m, 1).map_err(|e| e.on_line(no))?;
+                    let s2 = parse::capture(&m, 2).map_err(|e| e.on_line(no))?;
                     let c = Point { x: s1, y: s2 };
                     points.insert(c);
                 }
-                None => match REFX.captures(&line) {
+                None => match REFX.captures(line) {
                     Some(m) => {
-                        let s1 = String::from(&m[1]).parse().unwrap();
+                        let s1 = parse::capture(&m, 1).map_err(|e| e.on_line(no))?;
                         let c = Instruction::OnX(s1);
                         instr.push(c);
+                        fold_lines.push(no);
                     }
-                    None => match REFY.captures(&line) {
+                    None => match REFY.captures(line) {
                         Some(m) => {
-                            let s1 = String::from(&m[1]).parse().unwrap();
+                            let s1 = parse::capture(&m, 1).map_err(|e| e.on_line(no))?;
                             let c = Instruction::OnY(s1);
                             instr.push(c);
+                            fold_lines.push(no);
                         }
                         None => {
-                            panic!("fail");
+                            return Err(ParseError::new(
+                                no,
+                                1,
+                                "a point 'x,y' or 'fold along x=' / 'fold along y='",
+                            ));
                         }
                     },
                 },
             }
         }
     }
-
-    Sheet {
+    check_folds(&points, &instr, &fold_lines)?;
+    Ok(Sheet {
         point: points,
-        instr: instr,
+        instr,
+    })
+}
+
+/// Checks that every fold lays the part beyond its line onto the rest of
+/// the sheet, not past its edge.
+fn check_folds(
+    points: &HashSet<Point>,
+    instr: &[Instruction],
+    fold_lines: &[usize],
+) -> Result<(), ParseError> {
+    let mut max_x = points.iter().map(|p| p.x).max().unwrap_or(0);
+    let mut max_y = points.iter().map(|p| p.y).max().unwrap_or(0);
+    for (i, &no) in instr.iter().zip(fold_lines) {
+        let (max, line) = match i {
+            Instruction::OnX(x) => (&mut max_x, *x),
+            Instruction::OnY(y) => (&mut max_y, *y),
+        };
+        if *max - line > line {
+            let expected = format!("a fold line of at least {}", (*max + 1) / 2);
+            return Err(ParseError::new(no, "fold along x=".len() + 1, &expected));
+        }
+        *max = (*max).min(line - 1);
     }
+    Ok(())
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 4413);
+        answers::check(&Day13b);
+    }
+
+    #[test]
+    fn fold_frames() {
+        let input = "0,0\n4,0\n0,2\n\nfold along x=2";
+        let frames = frames(input).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!((frames[0].width(), frames[0].height()), (5, 3));
+        assert_eq!(
+            display(&fold(
+                &read_input(input).unwrap().point,
+                &Instruction::OnX(2)
+            )),
+            "#\n \n#"
+        );
+        assert_eq!(frames[1].iter().filter(|&&p| p).count(), 2);
+    }
+
+    #[test]
+    fn fold_past_the_edge() {
+        let e = read_input("5,5\n\nfold along y=1").err().unwrap();
+        assert_eq!(e.to_string(), "3:14 expected a fold line of at least 3");
+        let e = read_input("5,5\n\nfold along y=3\nfold along y=0")
+            .err()
+            .unwrap();
+        assert_eq!(e.to_string(), "4:14 expected a fold line of at least 1");
+        assert!(read_input("5,5\n\nfold along y=3\nfold along y=1\nfold along x=3").is_ok());
     }
 }
@@ -1,17 +1,36 @@
 use std::collections::HashMap;
-use std::path::Path;
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
-use crate::aoc::file;
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
 
-pub(crate) fn solve() -> u64 {
-    solve_file(&file::input("input14.txt"))
+pub(crate) struct Day14b;
+
+impl Solution for Day14b {
+    fn day(&self) -> u32 {
+        14
+    }
+
+    fn part(&self) -> char {
+        'b'
+    }
+
+    fn title(&self) -> &'static str {
+        "Extended Polymerization"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_input(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_polymer(solution::unbox(parsed)).into())
+    }
 }
 
-fn solve_file(f: &Path) -> u64 {
-    let mut p = read_input(f);
+fn solve_polymer(mut p: Polymere) -> u64 {
     p = p.pairs();
     for _ in 0..40 {
         p = p.apply();
@@ -67,48 +86,54 @@ impl Polymere {
     }
 }
 
-fn read_input(f: &Path) -> Polymere {
+fn read_input(input: &str) -> Result<Polymere, ParseError> {
     lazy_static! {
-        static ref RE: Regex = Regex::new(r"(\w+)\s*->\s*(\w+)").unwrap();
-        static ref RET: Regex = Regex::new(r"(\w+)").unwrap();
+        static ref RE: Regex = Regex::new(r"^(\w\w)\s*->\s*(\w)$").unwrap();
+        static ref RET: Regex = Regex::new(r"^(\w+)$").unwrap();
     }
-    let lines = file::read_lines(f).unwrap();
     let mut rules = HashMap::new();
     let mut temple = String::new();
-    for line in lines {
-        let line = line.unwrap();
+    for (no, line) in parse::lines(input) {
         if !line.is_empty() {
-            match RE.captures(&line) {
+            match RE.captures(line) {
                 Some(m) => {
-                    let s1 = String::from(&m[1]).parse().unwrap();
-                    let s2 = String::from(&m[2]).parse().unwrap();
+                    let s1 = String::from(&m[1]);
+                    let s2 = String::from(&m[2]);
                     rules.insert(s1, s2);
                 }
-                None => match RET.captures(&line) {
+                None => match RET.captures(line) {
                     Some(m) => {
                         let s1 = String::from(&m[1]);
                         temple = s1;
                     }
-                    None => panic!("fail"),
+                    None => {
+                        return Err(ParseError::new(
+                            no,
+                            1,
+                            "a polymer template or a rule like 'AB -> C'",
+                        ))
+                    }
                 },
             }
         }
     }
-    Polymere {
+    if temple.is_empty() {
+        return Err(ParseError::new(1, 1, "a polymer template"));
+    }
+    Ok(Polymere {
         template: temple,
-        rules: rules,
+        rules,
         pairs: HashMap::new(),
-    }
+    })
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 3306);
+        answers::check(&Day14b);
     }
 }
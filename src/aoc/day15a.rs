@@ -1,121 +1,72 @@
-use std::cmp::min;
-use std::path::Path;
+use crate::aoc::grid::Grid;
+use crate::aoc::parse::ParseError;
+use crate::aoc::shortest_path::dijkstra;
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
 
-use crate::aoc::file;
+pub(crate) struct Day15a;
 
-pub(crate) fn solve() -> u32 {
-    solve_file(&file::input("input15.txt"))
-}
-
-fn solve_file(f: &Path) -> u32 {
-    let levels = read_file(f);
-    let mut solver = Solver::create(levels);
-    solver.calculate();
-    solver.distance[solver.size_y - 1][solver.size_x - 1]
-}
-
-struct Solver {
-    levels: Vec<Vec<i8>>,
-    distance: Vec<Vec<u32>>,
-    size_x: usize,
-    size_y: usize,
-}
+impl Solution for Day15a {
+    fn day(&self) -> u32 {
+        15
+    }
 
-impl Solver {
-    fn create(levels: Vec<Vec<i8>>) -> Solver {
-        let size_x = levels[0].len();
-        let size_y = levels.len();
-        let mut distance = vec![];
-        distance.push(Self::create_initial_line(&levels, 0, 0));
-        for y in 1..size_y {
-            distance.push(Self::create_initial_line(
-                &levels,
-                y,
-                distance[y - 1][0] + levels[y][0] as u32,
-            ));
-        }
-        Solver {
-            levels: levels,
-            distance: distance,
-            size_x: size_x,
-            size_y: size_y,
-        }
+    fn part(&self) -> char {
+        'a'
     }
 
-    fn create_initial_line(levels: &Vec<Vec<i8>>, y: usize, left_value: u32) -> Vec<u32> {
-        let mut distline = vec![];
-        distline.push(left_value);
-        for x in 1..levels[y].len() {
-            distline.push(distline.last().unwrap() + levels[y][x] as u32)
-        }
-        distline
+    fn title(&self) -> &'static str {
+        "Chiton"
     }
 
-    fn calculate(&mut self) {
-        loop {
-            let mut changed = false;
-            for y in 0..self.size_y {
-                for x in 0..self.size_x {
-                    changed |= self.fix(x, y)
-                }
-            }
-            if !changed {
-                break;
-            }
-        }
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_file(input)?))
     }
 
-    fn fix(&mut self, x: usize, y: usize) -> bool {
-        //let dirs: Vec<Dir> = vec![Dir::R, Dir::D, Dir::U, Dir::L];
-        let mut min_dist = self.distance[y][x];
-        let level = self.levels[y][x];
-        if x > 0 {
-            min_dist = min(min_dist, level as u32 + self.distance[y][x - 1])
-        }
-        if y > 0 {
-            min_dist = min(min_dist, level as u32 + self.distance[y - 1][x])
-        }
-        if y < self.size_y - 1 {
-            min_dist = min(min_dist, level as u32 + self.distance[y + 1][x])
-        }
-        if x < self.size_x - 1 {
-            min_dist = min(min_dist, level as u32 + self.distance[y][x + 1])
-        }
-        if min_dist < self.distance[y][x] {
-            self.distance[y][x] = min_dist;
-            true
-        } else {
-            false
-        }
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_levels(solution::unbox(parsed)).into())
     }
 }
 
-fn read_file(p0: &Path) -> Vec<Vec<i8>> {
-    let input = file::read_lines(p0).unwrap();
-    input.map(|l| parse_line(&l.expect("fail"))).collect()
+fn solve_levels(levels: Grid<i8>) -> u32 {
+    let goal = (levels.width() - 1, levels.height() - 1);
+    dijkstra(&levels, (0, 0), goal).unwrap().cost
 }
 
-fn parse_line(line: &str) -> Vec<i8> {
-    line.chars()
-        .map(|c| c.to_digit(10).unwrap() as i8)
-        .collect()
+fn read_file(input: &str) -> Result<Grid<i8>, ParseError> {
+    let levels = Grid::parse_digits(input)?;
+    if levels.width() == 0 || levels.height() == 0 {
+        return Err(ParseError::at(1, "a map of risk levels"));
+    }
+    Ok(levels)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
+    use crate::aoc::file;
+
+    fn solve_input(input: &str) -> Result<u32, SolveError> {
+        Ok(solve_levels(read_file(input)?))
+    }
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 393);
+        answers::check(&Day15a);
     }
 
     #[test]
     fn result_sample() {
-        let result = solve_file(&file::input("input15-sample.txt"));
+        let result = solve_input(&file::read_input("input15-sample.txt").unwrap()).unwrap();
         println!("result : {}", result);
         assert_eq!(result, 40);
     }
+
+    #[test]
+    fn empty_map() {
+        for input in ["", "\n"] {
+            let e = read_file(input).err().unwrap();
+            assert_eq!(e.to_string(), "1:1 expected a map of risk levels");
+        }
+    }
 }
@@ -1,162 +1,221 @@
-use std::cmp::min;
-use std::path::Path;
+use crate::aoc::grid::Grid;
+use crate::aoc::netpbm::{self, Encoding};
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::shortest_path::{a_star, Route, WeightedGrid};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
 
-use crate::aoc::file;
+pub(crate) struct Day15b;
 
-pub(crate) fn solve() -> u32 {
-    solve_file(&file::input("input15.txt"))
+impl Solution for Day15b {
+    fn day(&self) -> u32 {
+        15
+    }
+
+    fn part(&self) -> char {
+        'b'
+    }
+
+    fn title(&self) -> &'static str {
+        "Chiton"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_file(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_tile(solution::unbox(parsed)).into())
+    }
 }
 
-fn solve_file(f: &Path) -> u32 {
-    let tile = read_file(f);
-    let levels = unfold(tile);
+fn solve_tile(tile: Grid<i8>) -> u32 {
+    cheapest_route(&Tiled::new(tile, 5, 9)).cost
+}
 
-    let mut solver = Solver::create(levels);
-    solver.calculate();
-    solver.distance[solver.size_y - 1][solver.size_x - 1]
+fn cheapest_route(map: &Tiled) -> Route {
+    let goal = (map.width() - 1, map.height() - 1);
+    a_star(map, (0, 0), goal).unwrap()
 }
 
-fn unfold(tile: Vec<Vec<i8>>) -> Vec<Vec<i8>> {
-    let a = unfold_x(tile);
-    let b = unfold_y(a);
-    b
+/// The full map: the tile repeated `factor` times right and down, each
+/// repetition with risk levels one higher, wrapping from `wrap` back to 1.
+pub(crate) struct Tiled {
+    tile: Grid<i8>,
+    factor: usize,
+    wrap: u32,
 }
 
-fn unfold_x(orig: Vec<Vec<i8>>) -> Vec<Vec<i8>> {
-    let mut r = vec![];
-    for y in 0..orig.len() {
-        let oline = &orig[y];
-        let mut rline = vec![];
-        for i in 0..5 {
-            for x in 0..oline.len() {
-                rline.push((oline[x] + i - 1) % 9 + 1);
-            }
-        }
-        r.push(rline);
+impl Tiled {
+    /// Panics unless `factor` and `wrap` are at least 1.
+    pub(crate) fn new(tile: Grid<i8>, factor: usize, wrap: u32) -> Tiled {
+        assert!(factor >= 1 && wrap >= 1, "factor and wrap must be positive");
+        Tiled { tile, factor, wrap }
     }
-    r
 }
 
-fn unfold_y(orig: Vec<Vec<i8>>) -> Vec<Vec<i8>> {
-    let mut r = vec![];
-    for i in 0..5 {
-        for y in 0..orig.len() {
-            let oline = &orig[y];
-            let mut rline = vec![];
-            for x in 0..oline.len() {
-                rline.push((oline[x] + i - 1) % 9 + 1);
-            }
-            r.push(rline);
+impl WeightedGrid for Tiled {
+    fn width(&self) -> usize {
+        self.tile.width() * self.factor
+    }
+
+    fn height(&self) -> usize {
+        self.tile.height() * self.factor
+    }
+
+    fn cost(&self, x: usize, y: usize) -> Option<u32> {
+        let (w, h) = (self.tile.width(), self.tile.height());
+        if x >= self.width() || y >= self.height() {
+            return None;
         }
+        let i = (x / w + y / h) as u32;
+        Some((self.tile[(x % w, y % h)] as u32 + i - 1) % self.wrap + 1)
     }
-    r
 }
 
-struct Solver {
-    levels: Vec<Vec<i8>>,
-    distance: Vec<Vec<u32>>,
-    size_x: usize,
-    size_y: usize,
+/// The cheapest route through the map tiled `factor` times, for looking at:
+/// as text, the risk levels with the route as '#', or as a PPM image.
+pub(crate) fn export_route(
+    input: &str,
+    factor: usize,
+    wrap: u32,
+    ppm: bool,
+) -> Result<Vec<u8>, ParseError> {
+    let map = Tiled::new(read_file(input)?, factor, wrap);
+    let route = cheapest_route(&map);
+    if ppm {
+        Ok(route_ppm(&map, &route))
+    } else {
+        Ok(route_text(&map, &route).into_bytes())
+    }
 }
 
-impl Solver {
-    fn create(levels: Vec<Vec<i8>>) -> Solver {
-        let size_x = levels[0].len();
-        let size_y = levels.len();
-        let mut distance = vec![];
-        distance.push(Self::create_initial_line(&levels, 0, 0));
-        for y in 1..size_y {
-            distance.push(Self::create_initial_line(
-                &levels,
-                y,
-                distance[y - 1][0] + levels[y][0] as u32,
-            ));
-        }
-        Solver {
-            levels: levels,
-            distance: distance,
-            size_x: size_x,
-            size_y: size_y,
-        }
+fn on_route(map: &Tiled, route: &Route) -> Grid<bool> {
+    let mut g = Grid::new(map.width(), map.height(), false);
+    for &p in &route.steps {
+        g[p] = true;
     }
+    g
+}
 
-    fn create_initial_line(levels: &Vec<Vec<i8>>, y: usize, left_value: u32) -> Vec<u32> {
-        let mut distline = vec![];
-        distline.push(left_value);
-        for x in 1..levels[y].len() {
-            distline.push(distline.last().unwrap() + levels[y][x] as u32)
-        }
-        distline
-    }
-
-    fn calculate(&mut self) {
-        let mut counter = 0u32;
-        loop {
-            counter += 1;
-            let mut changed = false;
-            for y in 0..self.size_y {
-                for x in 0..self.size_x {
-                    changed |= self.fix(x, y)
-                }
-            }
-            if !changed {
-                break;
+fn route_text(map: &Tiled, route: &Route) -> String {
+    let on_route = on_route(map, route);
+    let mut s = String::new();
+    for y in 0..map.height() {
+        for x in 0..map.width() {
+            if on_route[(x, y)] {
+                s.push('#');
+            } else {
+                // levels above 9 (with a larger wrap) continue with letters
+                s.push(char::from_digit(map.cost(x, y).unwrap(), 36).unwrap_or('?'));
             }
         }
-        println!("{} iterations", counter);
+        s.push('\n');
     }
+    s
+}
 
-    fn fix(&mut self, x: usize, y: usize) -> bool {
-        //let dirs: Vec<Dir> = vec![Dir::R, Dir::D, Dir::U, Dir::L];
-        let mut min_dist = self.distance[y][x];
-        let level = self.levels[y][x];
-        if x > 0 {
-            min_dist = min(min_dist, level as u32 + self.distance[y][x - 1])
-        }
-        if y > 0 {
-            min_dist = min(min_dist, level as u32 + self.distance[y - 1][x])
-        }
-        if y < self.size_y - 1 {
-            min_dist = min(min_dist, level as u32 + self.distance[y + 1][x])
-        }
-        if x < self.size_x - 1 {
-            min_dist = min(min_dist, level as u32 + self.distance[y][x + 1])
-        }
-        if min_dist < self.distance[y][x] {
-            self.distance[y][x] = min_dist;
-            true
-        } else {
-            false
+/// A binary PPM: risk levels in shades of grey, darker is riskier, the route
+/// in red.
+fn route_ppm(map: &Tiled, route: &Route) -> Vec<u8> {
+    let on_route = on_route(map, route);
+    let mut colours = Grid::new(map.width(), map.height(), [255, 0, 0]);
+    for (x, y) in on_route.positions() {
+        if !on_route[(x, y)] {
+            let level = map.cost(x, y).unwrap();
+            let grey = 255 - (255 * (level - 1) / map.wrap.max(2).saturating_sub(1)).min(255);
+            colours[(x, y)] = [grey as u16; 3];
         }
     }
+    netpbm::ppm(&colours, 255, Encoding::Binary)
 }
 
-fn read_file(p0: &Path) -> Vec<Vec<i8>> {
-    let input = file::read_lines(p0).unwrap();
-    input.map(|l| parse_line(&l.expect("fail"))).collect()
-}
-
-fn parse_line(line: &str) -> Vec<i8> {
-    line.chars()
-        .map(|c| c.to_digit(10).unwrap() as i8)
-        .collect()
+/// The risk levels run from 1 to 9; a route can only get more expensive
+/// with every step, which the A* search counts on.
+fn read_file(input: &str) -> Result<Grid<i8>, ParseError> {
+    let levels = Grid::parse(input, |c, column| match parse::digit(c, 10, column)? {
+        0 => Err(ParseError::at(
+            column,
+            "a risk level from 1 to 9, found '0'",
+        )),
+        d => Ok(d as i8),
+    })?;
+    if levels.width() == 0 || levels.height() == 0 {
+        return Err(ParseError::at(1, "a map of risk levels"));
+    }
+    Ok(levels)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
+    use crate::aoc::file;
+
+    fn solve_input(input: &str) -> Result<u32, SolveError> {
+        Ok(solve_tile(read_file(input)?))
+    }
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 2823);
+        answers::check(&Day15b);
+    }
+
+    #[test]
+    fn factor_one_is_the_tile() {
+        let tile = read_file(&file::read_input("input15.txt").unwrap()).unwrap();
+        assert_eq!(cheapest_route(&Tiled::new(tile, 1, 9)).cost, 393);
+    }
+
+    #[test]
+    fn wraps_after_nine() {
+        let tile = Grid::parse_digits("8").unwrap();
+        let map = Tiled::new(tile, 3, 9);
+        assert_eq!(map.cost(1, 0), Some(9));
+        assert_eq!(map.cost(2, 0), Some(1));
+        assert_eq!(map.cost(2, 2), Some(3));
+        assert_eq!(map.cost(3, 0), None);
+    }
+
+    #[test]
+    fn wraps_at_other_modulus() {
+        let tile = Grid::parse_digits("3").unwrap();
+        let map = Tiled::new(tile, 4, 4);
+        let levels: Vec<u32> = (0..4).map(|x| map.cost(x, 0).unwrap()).collect();
+        assert_eq!(levels, vec![3, 4, 1, 2]);
+    }
+
+    #[test]
+    fn route_as_text() {
+        let text = export_route("19\n11", 1, 9, false).unwrap();
+        assert_eq!(String::from_utf8(text).unwrap(), "#9\n##\n");
+    }
+
+    #[test]
+    fn route_as_ppm() {
+        let image = export_route("19\n11", 1, 9, true).unwrap();
+        let header = b"P6\n2 2\n255\n";
+        assert_eq!(&image[..header.len()], header);
+        assert_eq!(image.len(), header.len() + 2 * 2 * 3);
+        // the 9 off the route is black, the rest is the red route
+        assert_eq!(
+            &image[header.len()..],
+            &[255, 0, 0, 0, 0, 0, 255, 0, 0, 255, 0, 0]
+        );
     }
 
     #[test]
     fn result_sample() {
-        let result = solve_file(&file::input("input15-sample.txt"));
+        let result = solve_input(&file::read_input("input15-sample.txt").unwrap()).unwrap();
         println!("result : {}", result);
         assert_eq!(result, 315);
     }
+
+    #[test]
+    fn zero_risk() {
+        let e = read_file("19\n10").err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "2:2 expected a risk level from 1 to 9, found '0'"
+        );
+    }
 }
@@ -1,234 +1,337 @@
 use std::cmp::{max, min};
-use std::path::Path;
-use std::str::Chars;
+use std::fmt;
+use std::io::Read;
 
-use crate::aoc::file;
+use crate::aoc::bits::BitReader;
+use crate::aoc::packet::{self, Literal, Packet, PacketContent};
+use crate::aoc::parse::ParseError;
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
 
-pub(crate) fn solve() -> i64 {
-    solve_file(&file::input("input16.txt"))
-}
+pub(crate) struct Day16b;
 
-fn solve_file(f: &Path) -> i64 {
-    let hex = read_file(f);
-    let p = Packet::parse_string(&hex);
-    p.evaluate()
-}
+impl Solution for Day16b {
+    fn day(&self) -> u32 {
+        16
+    }
 
-struct Packet {
-    version: u32,
-    type_id: u32,
-    content: PacketContent,
-}
+    fn part(&self) -> char {
+        'b'
+    }
 
-enum PacketContent {
-    LiteralContent(u64),
-    OperatorContent(Vec<Packet>),
-}
+    fn title(&self) -> &'static str {
+        "Packet Decoder"
+    }
 
-impl Packet {
-    fn parse(r: &mut BitReader) -> Self {
-        let ver = r.read_bits(3);
-        let ptype = r.read_bits(3);
-        let content = if ptype == 4 {
-            Packet::parse_literal(r)
-        } else {
-            Packet::parse_operator(r)
-        };
-        Packet {
-            version: ver,
-            type_id: ptype,
-            content: content,
-        }
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(Packet::parse_string(&packet::read_hex(input)?)?))
     }
 
-    fn parse_string(hex: &str) -> Packet {
-        let mut r = BitReader::from(&hex);
-        Packet::parse(&mut r)
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solution::unbox::<Packet>(parsed).evaluate()?.into())
     }
+}
 
-    fn parse_literal(b: &mut BitReader) -> PacketContent {
-        let mut val = 0u64;
-        loop {
-            let word = b.read_bits(5);
-            if (word & 0x10) != 0 {
-                val = (val << 4) + (word & 0xf) as u64;
-            } else {
-                val = (val << 4) + word as u64;
-                break;
+impl Packet {
+    /// The value of the expression. Fails if a value does not fit in an
+    /// i64, or an operator has no sub-packets to work on.
+    fn evaluate(&self) -> Result<i64, SolveError> {
+        match &self.content {
+            PacketContent::LiteralContent(x) => Packet::literal_value(x),
+            PacketContent::OperatorContent(vec) => {
+                let values = vec.iter().map(|p| p.evaluate());
+                Packet::calc(self.type_id, values.collect::<Result<Vec<i64>, _>>()?)
             }
         }
-        PacketContent::LiteralContent(val)
-    }
-    fn parse_operator(b: &mut BitReader) -> PacketContent {
-        let length_type_id = b.read_bit().unwrap();
-        let packets: Vec<Packet> = if length_type_id == 0 {
-            let bit_len = b.read_bits(15) as usize;
-            Packet::read_packets_by_len(b, bit_len)
-        } else {
-            let packet_no = b.read_bits(11) as usize;
-            Packet::read_packets_by_number(b, packet_no)
-        };
-        PacketContent::OperatorContent(packets)
     }
 
-    fn read_packets_by_len(b: &mut BitReader, bit_len: usize) -> Vec<Packet> {
-        let mut packets = Vec::new();
-        let start = b.bits_read;
-        while b.bits_read - start < bit_len {
-            let packet = Packet::parse(b);
-            packets.push(packet);
-        }
-        packets
+    fn literal_value(x: &Literal) -> Result<i64, SolveError> {
+        let small = match x {
+            Literal::Small(v) => i64::try_from(*v).ok(),
+            Literal::Big(_) => None,
+        };
+        small.ok_or_else(|| SolveError::NoAnswer(format!("literal {} is not below 2^63", x)))
     }
 
-    fn read_packets_by_number(b: &mut BitReader, packet_no: usize) -> Vec<Packet> {
-        let mut packets = Vec::new();
-        for _ in 0..packet_no {
-            let packet = Packet::parse(b);
-            packets.push(packet);
-        }
-        packets
+    fn calc(type_id: u32, values: Vec<i64>) -> Result<i64, SolveError> {
+        let f: fn(i64, i64) -> Option<i64> = match type_id {
+            0 => i64::checked_add,
+            1 => i64::checked_mul,
+            2 => |a, b| Some(min(a, b)),
+            3 => |a, b| Some(max(a, b)),
+            5 => |a, b| Some(if a > b { 1 } else { 0 }),
+            6 => |a, b| Some(if a < b { 1 } else { 0 }),
+            7 => |a, b| Some(if a == b { 1 } else { 0 }),
+            _ => panic!("??"),
+        };
+        let (first, rest) = values.split_first().ok_or_else(|| {
+            SolveError::NoAnswer(format!("{}() has nothing to work on", operator(type_id)))
+        })?;
+        rest.iter()
+            .try_fold(*first, |a, &b| f(a, b))
+            .ok_or_else(|| {
+                let call = format!("{}({})", operator(type_id), join(&values));
+                SolveError::NoAnswer(format!("{} does not fit in 64 bits", call))
+            })
     }
 
-    fn evaluate(&self) -> i64 {
+    /// Evaluates like `evaluate`, adding a line for each packet to `lines`:
+    /// operators with the values of their sub-packets and their result,
+    /// indented below the operator they belong to.
+    fn trace(&self, depth: usize, lines: &mut Vec<String>) -> Result<i64, SolveError> {
+        let indent = "  ".repeat(depth);
         match &self.content {
-            PacketContent::LiteralContent(x) => *x as i64,
-            PacketContent::OperatorContent(vec) => match self.type_id {
-                0 => Packet::calc(&vec, |a, b| a + b),
-                1 => Packet::calc(&vec, |a, b| a * b),
-                2 => Packet::calc(&vec, |a, b| min(a, b)),
-                3 => Packet::calc(&vec, |a, b| max(a, b)),
-                5 => Packet::calc(&vec, |a, b| if a > b { 1 } else { 0 }),
-                6 => Packet::calc(&vec, |a, b| if a < b { 1 } else { 0 }),
-                7 => Packet::calc(&vec, |a, b| if a == b { 1 } else { 0 }),
-                _ => panic!("??"),
-            },
+            PacketContent::LiteralContent(x) => {
+                lines.push(format!("{}{}", indent, x));
+                Packet::literal_value(x)
+            }
+            PacketContent::OperatorContent(vec) => {
+                let line = lines.len();
+                lines.push(String::new());
+                let mut values = Vec::with_capacity(vec.len());
+                for p in vec {
+                    match p.trace(depth + 1, lines) {
+                        Ok(value) => values.push(value),
+                        Err(e) => {
+                            lines[line] =
+                                format!("{}{}(...) fails", indent, operator(self.type_id));
+                            return Err(e);
+                        }
+                    }
+                }
+                let call = format!("{}({})", operator(self.type_id), join(&values));
+                let value = Packet::calc(self.type_id, values);
+                lines[line] = match &value {
+                    Ok(value) => format!("{}{} = {}", indent, call, value),
+                    Err(_) => format!("{}{} fails", indent, call),
+                };
+                value
+            }
         }
     }
-
-    fn calc<T>(vec: &Vec<Packet>, f: T) -> i64
-    where
-        T: Fn(i64, i64) -> i64,
-    {
-        vec.iter().map(|p| p.evaluate()).reduce(f).unwrap()
-    }
-}
-
-fn read_file(p0: &Path) -> String {
-    let input = file::read_lines(p0).unwrap();
-    let l: Vec<String> = input.map(|x| x.unwrap()).collect();
-    l.get(0).unwrap().to_string()
 }
 
-struct BitReader<'a> {
-    chars: Chars<'a>,
-    current: Option<u8>,
-    bitno: u8,
-    bits_read: usize,
+fn join(values: &[i64]) -> String {
+    let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+    values.join(", ")
 }
 
-impl<'a> BitReader<'a> {
-    fn from(line: &'a str) -> Self {
-        //let l: &'a str = &line;
-        let x: Chars<'a> = line.chars();
-        BitReader {
-            chars: x,
-            current: None,
-            bitno: 0,
-            bits_read: 0,
-        }
+fn operator(type_id: u32) -> String {
+    match type_id {
+        0 => String::from("sum"),
+        1 => String::from("product"),
+        2 => String::from("min"),
+        3 => String::from("max"),
+        5 => String::from("gt"),
+        6 => String::from("lt"),
+        7 => String::from("eq"),
+        _ => format!("type{}", type_id),
     }
+}
 
-    fn read_bit(&mut self) -> Option<u32> {
-        match self.current {
-            None => {
-                let c_o = self.chars.next();
-                match c_o {
-                    None => {}
-                    Some(c) => {
-                        self.current = Some(c.to_digit(16).unwrap() as u8);
-                        self.bitno = 8;
+/// The transmission as an expression like `sum(3, min(7, 8))`.
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.content {
+            PacketContent::LiteralContent(x) => write!(f, "{}", x),
+            PacketContent::OperatorContent(vec) => {
+                write!(f, "{}(", operator(self.type_id))?;
+                for (i, p) in vec.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
                     }
+                    write!(f, "{}", p)?;
                 }
-            }
-            Some(_) => {}
-        }
-        match self.current {
-            None => None,
-            Some(bits) => {
-                let val = bits & self.bitno;
-                self.bitno = self.bitno >> 1;
-                if self.bitno == 0 {
-                    self.current = None;
-                }
-                self.bits_read += 1;
-                if val == 0 {
-                    Some(0)
-                } else {
-                    Some(1)
-                }
+                write!(f, ")")
             }
         }
     }
+}
 
-    fn read_bits(&mut self, n: usize) -> u32 {
-        let mut result = 0;
-        for _ in 0..n {
-            result = (result << 1) | self.read_bit().unwrap();
-        }
-        result
+/// The transmission as an expression and how it evaluates, step by step,
+/// up to a step that fails.
+pub(crate) fn explain(input: &str) -> Result<String, ParseError> {
+    Ok(explain_packet(&Packet::parse_string(&packet::read_hex(
+        input,
+    )?)?))
+}
+
+/// Like `explain`, for a transmission in raw bytes instead of hex digits.
+pub(crate) fn explain_binary(bytes: &[u8]) -> Result<String, ParseError> {
+    Ok(explain_packet(&Packet::parse(&mut BitReader::from_bytes(
+        bytes,
+    ))?))
+}
+
+/// Like `explain_binary`, reading the bytes as they come, e.g. from stdin.
+/// A cut off transmission only shows when the bytes run out.
+pub(crate) fn explain_stream<R: Read>(reader: R) -> Result<String, ParseError> {
+    Ok(explain_packet(&Packet::parse(
+        &mut BitReader::from_reader(reader),
+    )?))
+}
+
+fn explain_packet(p: &Packet) -> String {
+    let mut lines = vec![p.to_string(), String::new()];
+    if let Err(e) = p.trace(0, &mut lines) {
+        lines.push(e.to_string());
     }
+    lines.join("\n") + "\n"
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
+    use crate::aoc::bits::BitWriter;
 
     #[test]
     fn result() {
-        let result = solve();
-        println!("result : {}", result);
-        assert_eq!(result, 6802496672062);
+        answers::check(&Day16b);
     }
 
     #[test]
     fn result_sample2() {
-        let p = Packet::parse_string("04005AC33890");
-        assert_eq!(p.evaluate(), 54)
+        let p = Packet::parse_string("04005AC33890").unwrap();
+        assert_eq!(p.evaluate().unwrap(), 54)
     }
 
     #[test]
     fn result_sample3() {
-        let p = Packet::parse_string("880086C3E88112");
-        assert_eq!(p.evaluate(), 7)
+        let p = Packet::parse_string("880086C3E88112").unwrap();
+        assert_eq!(p.evaluate().unwrap(), 7)
     }
     #[test]
     fn result_sample4() {
-        let p = Packet::parse_string("CE00C43D881120");
-        assert_eq!(p.evaluate(), 9)
+        let p = Packet::parse_string("CE00C43D881120").unwrap();
+        assert_eq!(p.evaluate().unwrap(), 9)
     }
 
     #[test]
     fn result_sample5() {
-        let p = Packet::parse_string("D8005AC2A8F0");
-        assert_eq!(p.evaluate(), 1)
+        let p = Packet::parse_string("D8005AC2A8F0").unwrap();
+        assert_eq!(p.evaluate().unwrap(), 1)
     }
 
     #[test]
     fn result_sample6() {
-        let p = Packet::parse_string("F600BC2D8F");
-        assert_eq!(p.evaluate(), 0)
+        let p = Packet::parse_string("F600BC2D8F").unwrap();
+        assert_eq!(p.evaluate().unwrap(), 0)
     }
 
     #[test]
     fn result_sample7() {
-        let p = Packet::parse_string("9C005AC2F8F0");
-        assert_eq!(p.evaluate(), 0)
+        let p = Packet::parse_string("9C005AC2F8F0").unwrap();
+        assert_eq!(p.evaluate().unwrap(), 0)
     }
 
     #[test]
     fn result_sample8() {
-        let p = Packet::parse_string("9C0141080250320F1802104A08");
-        assert_eq!(p.evaluate(), 1)
+        let p = Packet::parse_string("9C0141080250320F1802104A08").unwrap();
+        assert_eq!(p.evaluate().unwrap(), 1)
+    }
+
+    #[test]
+    fn expression() {
+        let p = Packet::parse_string("9C0141080250320F1802104A08").unwrap();
+        assert_eq!(p.to_string(), "eq(sum(1, 3), product(2, 2))");
+    }
+
+    #[test]
+    fn evaluation_trace() {
+        let lines = explain("9C0141080250320F1802104A08").unwrap();
+        let expected = "eq(sum(1, 3), product(2, 2))\n\n\
+                        eq(4, 4) = 1\n  sum(1, 3) = 4\n    1\n    3\n  product(2, 2) = 4\n    2\n    2\n";
+        assert_eq!(lines, expected);
+    }
+
+    #[test]
+    fn overflow() {
+        let big = || Packet::literal(0, 1 << 62);
+        let p = Packet::operator(0, vec![big(), big()]);
+        let e = p.evaluate().unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "sum(4611686018427387904, 4611686018427387904) does not fit in 64 bits"
+        );
+        // the comparison does not care how large its operands are
+        let p = Packet::operator(5, vec![big(), Packet::literal(0, 1)]);
+        assert_eq!(p.evaluate().unwrap(), 1);
+    }
+
+    #[test]
+    fn no_sub_packets() {
+        let e = Packet::operator(1, vec![]).evaluate().unwrap_err();
+        assert_eq!(e.to_string(), "product() has nothing to work on");
+        // not a fault of the input as such, so there is no position to give
+        let e = Day16b.solve(&Packet::operator(0, vec![]).encode());
+        assert_eq!(
+            e.unwrap_err().in_file("input16.txt").to_string(),
+            "input16.txt: sum() has nothing to work on"
+        );
+    }
+
+    #[test]
+    fn binary_transmission() {
+        let bytes: &[u8] = &[
+            0x9C, 0x01, 0x41, 0x08, 0x02, 0x50, 0x32, 0x0F, 0x18, 0x02, 0x10, 0x4A, 0x08,
+        ];
+        let expected = explain("9C0141080250320F1802104A08").unwrap();
+        assert_eq!(explain_binary(bytes).unwrap(), expected);
+        assert_eq!(explain_stream(bytes).unwrap(), expected);
+    }
+
+    #[test]
+    fn truncated_binary_transmission() {
+        let e = explain_binary(&[0x9C, 0x01, 0x41, 0x08]).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "1:4 expected sub-packets of 80 bits, found only 10 bits"
+        );
+    }
+
+    #[test]
+    fn trace_up_to_overflow() {
+        let p = Packet::operator(
+            3,
+            vec![Packet::operator(
+                1,
+                (0..3)
+                    .map(|_| Packet::literal(0, u32::MAX as u64))
+                    .collect(),
+            )],
+        );
+        let lines = explain(&p.encode()).unwrap();
+        assert!(lines.ends_with(
+            "max(...) fails\n  product(4294967295, 4294967295, 4294967295) fails\n    \
+             4294967295\n    4294967295\n    4294967295\n\
+             product(4294967295, 4294967295, 4294967295) does not fit in 64 bits\n"
+        ));
+    }
+
+    #[test]
+    fn literal_too_wide() {
+        let mut w = BitWriter::new();
+        w.write_bits(0b000_100, 6);
+        for _ in 0..16 {
+            w.write_bits(0x1f, 5);
+        }
+        w.write_bits(0, 5);
+        let p = Packet::parse_string(&w.to_hex()).unwrap();
+        let e = p.evaluate().unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "literal 295147905179352825840 is not below 2^63"
+        );
+    }
+
+    #[test]
+    fn literal_at_64_bits() {
+        let p = Packet::parse_string(&Packet::literal(0, u64::MAX).encode()).unwrap();
+        assert_eq!(p, Packet::literal(0, u64::MAX));
+        let e = p.evaluate().unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "literal 18446744073709551615 is not below 2^63"
+        );
     }
 }
@@ -1,5 +1,69 @@
 use std::collections::HashSet;
 
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
+
+pub(crate) struct Day17b;
+
+impl Solution for Day17b {
+    fn day(&self) -> u32 {
+        17
+    }
+
+    fn part(&self) -> char {
+        'b'
+    }
+
+    fn title(&self) -> &'static str {
+        "Trick Shot"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_target(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        let (x0, x1, y0, y1) = solution::unbox(parsed);
+        Ok(result(x0, x1, y0, y1).into())
+    }
+}
+
+fn read_target(input: &str) -> Result<(i32, i32, i32, i32), ParseError> {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r"target area: x=(-?\d+)\.\.(-?\d+), y=(-?\d+)\.\.(-?\d+)").unwrap();
+    }
+    let m = RE
+        .captures(input)
+        .ok_or_else(|| ParseError::at(1, "'target area: x=..., y=...'"))?;
+    let (x0, x1) = range(&m, 1)?;
+    let (y0, y1) = range(&m, 3)?;
+    Ok((x0, x1, y0, y1))
+}
+
+/// The bounds captured by groups `i` and `i + 1`, lowest first. The search
+/// tries velocities up to 1000, so they have to be no further out.
+fn range(m: &Captures, i: usize) -> Result<(i32, i32), ParseError> {
+    let column = |i: usize| m.get(i).unwrap().start() + 1;
+    let (from, to): (i32, i32) = (parse::capture(m, i)?, parse::capture(m, i + 1)?);
+    for (j, c) in [(i, from), (i + 1, to)] {
+        if c.abs() > 1000 {
+            return Err(ParseError::at(
+                column(j),
+                "a coordinate between -1000 and 1000",
+            ));
+        }
+    }
+    if from > to {
+        let expected = format!("a number of at least {}", from);
+        return Err(ParseError::at(column(i + 1), &expected));
+    }
+    Ok((from, to))
+}
+
 fn get_velocities_x(xl: i32, xr: i32) -> Vec<(i32, i32)> {
     let mut ret = Vec::new();
     for vel in (1..xr + 1).rev() {
@@ -7,7 +71,7 @@ fn get_velocities_x(xl: i32, xr: i32) -> Vec<(i32, i32)> {
         let mut x = 0;
         'i: for i in 1..1000 {
             x += v;
-            v -= x.signum();
+            v -= v.signum();
             if x >= xl && x <= xr {
                 ret.push((i, vel))
             } else if x > xr {
@@ -47,9 +111,6 @@ fn result(x0: i32, x1: i32, y0: i32, y1: i32) -> i32 {
             }
         }
     }
-    for (x, y) in &vecs {
-        println!("{},{}", x, y)
-    }
     vecs.len() as i32
 }
 
@@ -57,11 +118,11 @@ fn result(x0: i32, x1: i32, y0: i32, y1: i32) -> i32 {
 mod test {
 
     use super::*;
+    use crate::aoc::answers;
 
     #[test]
     fn result1() {
-        let res = result(241, 273, -97, -63);
-        assert_eq!(res, 4656)
+        answers::check(&Day17b);
     }
 
     #[test]
@@ -69,4 +130,21 @@ mod test {
         let res = result(20, 30, -10, -5);
         assert_eq!(res, 112)
     }
+
+    #[test]
+    fn reversed_range() {
+        let e = read_target("target area: x=5..1, y=-5..-10").unwrap_err();
+        assert_eq!(e.to_string(), "1:19 expected a number of at least 5");
+        let e = read_target("target area: x=1..5, y=-5..-10").unwrap_err();
+        assert_eq!(e.to_string(), "1:28 expected a number of at least -5");
+    }
+
+    #[test]
+    fn far_out() {
+        let e = read_target("target area: x=1..5, y=-2000000000..-10").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "1:24 expected a coordinate between -1000 and 1000"
+        );
+    }
 }
@@ -1,175 +1,217 @@
-use std::ops::Mul;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
 
-use lazy_static::lazy_static;
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
+use crate::aoc::space::{Vec3, ROTATIONS};
 
-use crate::aoc::file;
+pub(crate) struct Day19a;
 
-pub(crate) fn solve() -> u32 {
-    solve_file(&file::input("input19.txt"))
-}
+impl Solution for Day19a {
+    fn day(&self) -> u32 {
+        19
+    }
+
+    fn part(&self) -> char {
+        'a'
+    }
+
+    fn title(&self) -> &'static str {
+        "Beacon Scanner"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_file(input)?))
+    }
 
-fn solve_file(f: &Path) -> u32 {
-    let trees = read_file(f);
-    0
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_scanners(solution::unbox(parsed))?.into())
+    }
 }
 
-fn read_file(f: &Path) -> u32 {
-    let lines = file::read_lines(f).unwrap();
-    for line in lines {}
-    0
+fn solve_scanners(scanners: Vec<Scanner>) -> Result<usize, SolveError> {
+    let placed = align(&scanners)?;
+    let beacons: HashSet<Vec3> = placed.iter().flat_map(|p| p.beacons.clone()).collect();
+    Ok(beacons.len())
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-struct Pos {
-    x: i32,
-    y: i32,
-    z: i32,
+/// Beacons in common that prove two scanners overlap.
+const OVERLAP: usize = 12;
+
+/// What one scanner reports, relative to itself, in its own orientation.
+pub(crate) struct Scanner {
+    beacons: Vec<Vec3>,
 }
 
-struct Matrix {
-    m: Vec<Vec<i32>>,
+/// A scanner placed relative to the first one: where it is, and its beacons
+/// turned and moved to the first scanner's coordinates.
+pub(crate) struct Placed {
+    pub(crate) position: Vec3,
+    pub(crate) beacons: Vec<Vec3>,
 }
 
-lazy_static! {
-    static ref IDENTITY: Matrix = Matrix {
-                m: vec![            vec![1, 0, 0], //
-            vec![0, 1, 0], //
-            vec![0, 0, 1]]};
-
-static ref ROT_X: Matrix = Matrix {
-    m: vec![
-        //
-        vec![1, 0, 0],  //
-        vec![0, 0, -1], //
-        vec![0, 1, 0],
-    ]};
-
-static ref ROT_Y: Matrix = Matrix {
-    m: vec![
-        //
-        vec![0, 0, 1], //
-        vec![0, 1, 0], //
-        vec![-1, 0, 0],
-    ],
-};
-
-static ref ROT_Z: Matrix = Matrix {
-    m: vec![
-        //
-        vec![0, -1, 0], //
-        vec![1, 0, 0],  //
-        vec![0, 0, 1],
-    ],
-};
-
-static ref ALL_ROTS: Vec<&'static Matrix> = vec![
-    //
-    &IDENTITY,             //
-    &ROT_X,                 //
-//    (&ROT_X) * &ROT_X,         //
-//    ROT_X * ROT_X *ROT_X, //
-];
-    }
-
-impl Matrix {
-    fn lines(&self) -> usize {
-        self.m.len()
-    }
-
-    fn columns(&self) -> usize {
-        self.m[0].len()
-    }
-
-    fn mult_pos(&self, pos: &Pos) -> Pos {
-        let po = pos.to_matrix();
-        let mul = self * &po;
-        Pos::from(&mul)
+pub(crate) fn read_file(input: &str) -> Result<Vec<Scanner>, ParseError> {
+    let mut scanners: Vec<Scanner> = Vec::new();
+    for (no, line) in parse::lines(input) {
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with("---") {
+            let header = format!("--- scanner {} ---", scanners.len());
+            if line != header {
+                return Err(ParseError::new(no, 1, &format!("'{}'", header)));
+            }
+            scanners.push(Scanner { beacons: vec![] });
+            continue;
+        }
+        let scanner = scanners
+            .last_mut()
+            .ok_or_else(|| ParseError::new(no, 1, "'--- scanner 0 ---'"))?;
+        scanner
+            .beacons
+            .push(read_beacon(line).map_err(|e| e.on_line(no))?);
+    }
+    if scanners.is_empty() {
+        return Err(ParseError::at(1, "'--- scanner 0 ---'"));
     }
+    Ok(scanners)
 }
 
-impl<'a, 'b> Mul<&'b Matrix> for &'a Matrix {
-    type Output = Matrix;
-
-    fn mul(self, o: &'b Matrix) -> Matrix {
-        if self.columns() != o.lines() {
-            panic!("incompatible");
+/// Places every scanner, starting from the first one, by finding one it
+/// overlaps with among those already placed. Fails if one overlaps none of
+/// them.
+pub(crate) fn align(scanners: &[Scanner]) -> Result<Vec<Placed>, SolveError> {
+    let mut placed: Vec<Option<Placed>> = scanners.iter().map(|_| None).collect();
+    placed[0] = Some(Placed {
+        position: Vec3::ZERO,
+        beacons: scanners[0].beacons.clone(),
+    });
+    let mut todo = vec![0];
+    while let Some(i) = todo.pop() {
+        for j in 0..scanners.len() {
+            if placed[j].is_some() {
+                continue;
+            }
+            let known = &placed[i].as_ref().unwrap().beacons;
+            if let Some(p) = overlap(known, &scanners[j].beacons) {
+                placed[j] = Some(p);
+                todo.push(j);
+            }
         }
-        let mut m = vec![];
-        for li in 0..self.lines() {
-            let line = &self.m[li];
-            let mut nline = vec![];
-            for oci in 0..o.columns() {
-                let mut sum = 0;
-                for k in 0..line.len() {
-                    sum += line[k] * o.m[k][oci];
+    }
+    placed
+        .into_iter()
+        .enumerate()
+        .map(|(i, p)| {
+            p.ok_or_else(|| {
+                SolveError::NoAnswer(format!("scanner {} overlaps none of the others", i))
+            })
+        })
+        .collect()
+}
+
+/// Tries every orientation of `beacons` for one that shares at least
+/// `OVERLAP` beacons with `known` when moved by the same offset.
+fn overlap(known: &[Vec3], beacons: &[Vec3]) -> Option<Placed> {
+    for rot in ROTATIONS {
+        let turned: Vec<Vec3> = beacons.iter().map(|&b| rot * b).collect();
+        let mut offsets: HashMap<Vec3, usize> = HashMap::new();
+        for k in known {
+            for t in &turned {
+                let count = offsets.entry(*k - *t).or_insert(0);
+                *count += 1;
+                if *count >= OVERLAP {
+                    let position = *k - *t;
+                    return Some(Placed {
+                        position,
+                        beacons: turned.iter().map(|&t| t + position).collect(),
+                    });
                 }
-                nline.push(sum)
             }
-            m.push(nline);
         }
-        Matrix { m: m }
     }
+    None
 }
 
-impl Pos {
-    fn to_matrix(&self) -> Matrix {
-        Matrix {
-            m: vec![
-                //
-                vec![self.x], //
-                vec![self.y], //
-                vec![self.z],
-            ],
-        }
+/// "x,y,z"
+fn read_beacon(line: &str) -> Result<Vec3, ParseError> {
+    let fields: Vec<&str> = line.split(',').collect();
+    if fields.len() != 3 {
+        return Err(ParseError::at(1, "three coordinates 'x,y,z'"));
     }
+    let mut column = 1;
+    let mut coords = [0; 3];
+    for (i, f) in fields.iter().enumerate() {
+        coords[i] = parse::number(f, column)?;
+        column += f.len() + 1;
+    }
+    Ok(Vec3::new(coords[0], coords[1], coords[2]))
+}
 
-    fn from(m: &Matrix) -> Pos {
-        assert_eq!(m.lines(), 3);
-        assert_eq!(m.columns(), 1);
-        Pos {
-            x: m.m[0][0],
-            y: m.m[1][0],
-            z: m.m[2][0],
+/// Input for three scanners, turned differently, that see 18 beacons each
+/// and 12 of them also seen by the next: 30 beacons in all, with the scanners
+/// at (0,0,0), (200,-30,10) and (420,50,-60).
+#[cfg(test)]
+pub(crate) fn sample() -> String {
+    // scattered without pattern, a regular one overlaps itself when moved
+    let scatter = |n: i32| (n * n * 7919 + n * 104_729) % 1_000 - 500;
+    let beacon = |i: i32| Vec3::new(scatter(3 * i + 1), scatter(3 * i + 2), scatter(3 * i + 3));
+    let scanners = [
+        (Vec3::ZERO, 0, 0..18),
+        (Vec3::new(200, -30, 10), 7, 6..24),
+        (Vec3::new(420, 50, -60), 19, 12..30),
+    ];
+    let mut s = String::new();
+    for (n, (position, rot, beacons)) in scanners.into_iter().enumerate() {
+        s += &format!("--- scanner {} ---\n", n);
+        let inverse = ROTATIONS[rot].inverse().unwrap();
+        for i in beacons {
+            let b = inverse * (beacon(i) - position);
+            s += &format!("{},{},{}\n", b.x, b.y, b.z);
         }
+        s += "\n";
     }
+    s
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::file;
+
+    fn solve_input(input: &str) -> Result<usize, SolveError> {
+        solve_scanners(read_file(input)?)
+    }
 
     #[test]
-    fn result() {
-        let result = solve();
+    fn result_sample() {
+        let result = solve_input(&file::read_input("input19-sample.txt").unwrap()).unwrap();
         println!("result : {}", result);
-        assert_eq!(result, 3665);
+        assert_eq!(result, 79);
     }
 
     #[test]
-    fn rotx() {
-        let po = Pos { x: 1, y: 2, z: 3 };
-        let result = ROT_X.mult_pos(&po);
-        assert_eq!(result, Pos { x: 1, y: -3, z: 2 });
+    fn three_scanners() {
+        assert_eq!(solve_input(&sample()).unwrap(), 30);
     }
+
     #[test]
-    fn rotxx() {
-        let po = Pos { x: 1, y: 2, z: 3 };
-        let result = (&ROT_X as &Matrix * &ROT_X as &Matrix).mult_pos(&po);
-        assert_eq!(result, Pos { x: 1, y: -2, z: -3 });
+    fn scanner_out_of_reach() {
+        let input = "--- scanner 0 ---\n1,2,3\n\n--- scanner 1 ---\n4,5,6";
+        let e = solve_input(input).unwrap_err();
+        assert_eq!(e.to_string(), "scanner 1 overlaps none of the others");
     }
 
     #[test]
-    fn roty() {
-        let po = Pos { x: 1, y: 2, z: 3 };
-        let result = ROT_Y.mult_pos(&po);
-        assert_eq!(result, Pos { x: 3, y: 2, z: -1 });
+    fn bad_header() {
+        let e = read_file("--- scanner 0 ---\n1,2,3\n--- scanner 2 ---")
+            .err()
+            .unwrap();
+        assert_eq!(e.to_string(), "3:1 expected '--- scanner 1 ---'");
     }
 
     #[test]
-    fn rotz() {
-        let po = Pos { x: 1, y: 2, z: 3 };
-        let result = ROT_Z.mult_pos(&po);
-        assert_eq!(result, Pos { x: -2, y: 1, z: 3 });
+    fn bad_beacon() {
+        let e = read_file("--- scanner 0 ---\n1,x,3").err().unwrap();
+        assert_eq!(e.to_string(), "2:3 expected a number, found 'x'");
     }
 }
@@ -0,0 +1,63 @@
+use crate::aoc::day19a::{align, read_file, Scanner};
+use crate::aoc::parse::ParseError;
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
+
+pub(crate) struct Day19b;
+
+impl Solution for Day19b {
+    fn day(&self) -> u32 {
+        19
+    }
+
+    fn part(&self) -> char {
+        'b'
+    }
+
+    fn title(&self) -> &'static str {
+        "Beacon Scanner"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_file(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_scanners(solution::unbox(parsed))?.into())
+    }
+}
+
+/// The largest Manhattan distance between any two scanners.
+fn solve_scanners(scanners: Vec<Scanner>) -> Result<i32, SolveError> {
+    let placed = align(&scanners)?;
+    let mut max = 0;
+    for a in &placed {
+        for b in &placed {
+            max = max.max((a.position - b.position).manhattan());
+        }
+    }
+    Ok(max)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::aoc::day19a;
+    use crate::aoc::file;
+
+    fn solve_input(input: &str) -> Result<i32, SolveError> {
+        solve_scanners(read_file(input)?)
+    }
+
+    #[test]
+    fn result_sample() {
+        let result = solve_input(&file::read_input("input19-sample.txt").unwrap()).unwrap();
+        println!("result : {}", result);
+        assert_eq!(result, 3621);
+    }
+
+    #[test]
+    fn three_scanners() {
+        // from (0,0,0) to (420,50,-60)
+        assert_eq!(solve_input(&day19a::sample()).unwrap(), 530);
+    }
+}
@@ -1,107 +1,125 @@
-use std::path::Path;
-
-use crate::aoc::file;
-
-impl Data {
-    fn turn(&mut self, border: char) {
-        self.enlarge(border);
-        self.enlarge(border);
-        let mut f2 = vec![];
-        for y in 1..self.field.len() - 1 {
-            let mut line = vec![];
-            for x in 1..self.field[0].len() - 1 {
-                let code = self.get_index(x, y);
-                line.push(self.decode_code(code))
-            }
-            f2.push(line)
-        }
-        self.field = f2;
-    }
-    fn enlarge(&mut self, border: char) {
-        let len = self.field[0].len();
-        self.field.insert(0, vec![border; len]);
-        self.field.push(vec![border; len]);
-        for x in &mut self.field {
-            x.insert(0, border);
-            x.push(border);
-        }
+use crate::aoc::grid::Grid;
+use crate::aoc::image::Image;
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
+
+pub(crate) struct Day20a;
+
+impl Solution for Day20a {
+    fn day(&self) -> u32 {
+        20
     }
 
-    fn get_index(&self, x: usize, y: usize) -> usize {
-        (self.get_bit(x - 1, y - 1) << 8)
-            + (self.get_bit(x, y - 1) << 7)
-            + (self.get_bit(x + 1, y - 1) << 6)
-            + (self.get_bit(x - 1, y) << 5)
-            + (self.get_bit(x, y) << 4)
-            + (self.get_bit(x + 1, y) << 3)
-            + (self.get_bit(x - 1, y + 1) << 2)
-            + (self.get_bit(x, y + 1) << 1)
-            + (self.get_bit(x + 1, y + 1))
+    fn part(&self) -> char {
+        'a'
     }
 
-    fn get_bit(&self, x: usize, y: usize) -> usize {
-        let c = self.field[y][x];
-        match c {
-            '#' => 1,
-            _ => 0,
-        }
+    fn title(&self) -> &'static str {
+        "Trench Map"
     }
 
-    fn decode_code(&self, idx: usize) -> char {
-        self.decode.as_bytes()[idx] as char
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_file(input)?))
     }
 
-    fn count_hashes(self) -> usize {
-        self.field
-            .iter()
-            .map(|l| l.iter().filter(|c| **c == '#').count())
-            .sum()
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(lit_after(solution::unbox(parsed), 2)?.into())
     }
 }
 
-fn read_file(f: &Path) -> Data {
-    let mut lines = file::read_lines(f).unwrap();
-    let decode = lines.next().unwrap().unwrap();
-    let _empty = lines.next();
-    let mut vec = vec![];
+/// The pixels lit after enhancing the image `n` times.
+pub(crate) fn lit_after(mut image: Image, n: usize) -> Result<usize, SolveError> {
+    image.enhance(n);
+    image.lit().ok_or_else(|| {
+        SolveError::NoAnswer(format!(
+            "infinitely many pixels are lit after {} enhancements",
+            n
+        ))
+    })
+}
 
-    for line in lines {
-        let l: Vec<char> = line.unwrap().chars().collect();
-        vec.push(l)
+/// The pixels kept of the image and of every enhancement of it, `n` in all.
+/// A lit background is not shown.
+pub(crate) fn frames(input: &str, n: usize) -> Result<Vec<Grid<bool>>, ParseError> {
+    let mut image = read_file(input)?;
+    let mut frames = vec![image.pixels().clone()];
+    for _ in 0..n {
+        image.enhance(1);
+        frames.push(image.pixels().clone());
     }
+    Ok(frames)
+}
 
-    Data {
-        decode: decode,
-        field: vec,
+pub(crate) fn read_file(input: &str) -> Result<Image, ParseError> {
+    let mut lines = parse::lines(input);
+    let decode = match lines.next() {
+        Some((_, line)) => pixels(line)?,
+        None => return Err(ParseError::at(1, "an enhancement algorithm")),
+    };
+    let algorithm: [bool; 512] = decode
+        .as_slice()
+        .try_into()
+        .map_err(|_| ParseError::at(decode.len() + 1, "512 pixels in the algorithm"))?;
+    let _empty = lines.next();
+    let field = Grid::from_lines(lines, pixel)?;
+    if field.height() == 0 {
+        return Err(ParseError::new(3, 1, "an input image"));
     }
+    Ok(Image::new(algorithm, field))
+}
+
+fn pixels(line: &str) -> Result<Vec<bool>, ParseError> {
+    line.chars()
+        .enumerate()
+        .map(|(i, c)| pixel(c, i + 1))
+        .collect()
 }
 
-struct Data {
-    decode: String,
-    field: Vec<Vec<char>>,
+/// '#' for a lit pixel, '.' for a dark one.
+fn pixel(c: char, column: usize) -> Result<bool, ParseError> {
+    match c {
+        '#' | '.' => Ok(c == '#'),
+        _ => Err(ParseError::at(
+            column,
+            &format!("'#' or '.', found '{}'", c),
+        )),
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
+    use crate::aoc::file;
 
     #[test]
     fn result() {
-        let mut inp = read_file(&file::input("input20.txt"));
-        inp.turn('.');
-        inp.turn('#');
-        let result = inp.count_hashes();
-        println!("result : {}", result);
-        assert_eq!(result, 5395);
+        answers::check(&Day20a);
     }
 
     #[test]
     fn sample() {
-        let mut inp = read_file(&file::input("input20-sample.txt"));
-        inp.turn('.');
-        inp.turn('.');
-        let result = inp.count_hashes();
-        println!("result : {}", result);
+        let result = lit_after(
+            read_file(&file::read_input("input20-sample.txt").unwrap()).unwrap(),
+            2,
+        )
+        .unwrap();
         assert_eq!(result, 35);
     }
+
+    #[test]
+    fn short_algorithm() {
+        let e = read_file("#.#\n\n#").err().unwrap();
+        assert_eq!(e.to_string(), "1:4 expected 512 pixels in the algorithm");
+    }
+
+    #[test]
+    fn lit_background() {
+        let input = format!("{}\n\n#", "#".repeat(512));
+        let e = lit_after(read_file(&input).unwrap(), 2).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "infinitely many pixels are lit after 2 enhancements"
+        );
+    }
 }
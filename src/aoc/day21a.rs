@@ -1,83 +1,335 @@
-use std::path::Path;
+use std::fmt;
 
-use crate::aoc::file;
+use crate::aoc::day21b::{read_positions, DiracGame};
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
 
-struct Player {
-    pos: u32,
-    score: u32,
+pub(crate) struct Day21a;
+
+impl Solution for Day21a {
+    fn day(&self) -> u32 {
+        21
+    }
+
+    fn part(&self) -> char {
+        'a'
+    }
+
+    fn title(&self) -> &'static str {
+        "Dirac Dice"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_positions(input, &DiracGame::PRACTICE)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_positions(solution::unbox(parsed)).into())
+    }
+}
+
+fn solve_positions(positions: Vec<u32>) -> u32 {
+    let rules = DiracGame::PRACTICE;
+    let die = DeterministicDie::new(rules.sides);
+    let mut game = Game::new(rules, &positions, Box::new(die));
+    game.play().answer()
+}
+
+/// The practice game played with `die` by as many players as the input has
+/// lines: every turn, then who won.
+pub(crate) fn practice(input: &str, die: Box<dyn Die>) -> Result<String, ParseError> {
+    let rules = DiracGame {
+        players: parse::lines(input).count().max(1),
+        ..DiracGame::PRACTICE
+    };
+    let positions = read_positions(input, &rules)?;
+    let mut game = Game::new(rules, &positions, die);
+    let outcome = game.play();
+    let mut s = String::new();
+    for turn in &game.log {
+        s += &format!("{}\n", turn);
+    }
+    s += &format!("{}\n", outcome);
+    Ok(s)
 }
 
-struct Die {
+/// Something to roll, one number at a time.
+pub(crate) trait Die {
+    fn roll(&mut self) -> u32;
+}
+
+/// Rolls 1, 2, 3 and so on up to `sides`, then starts over.
+pub(crate) struct DeterministicDie {
+    sides: u32,
     next: u32,
-    rolls: u32,
 }
 
-struct Game {
-    p1: Player,
-    p2: Player,
-    die: Die,
+/// Rolls 1 to `sides` at random, the same ones for the same seed.
+pub(crate) struct SeededDie {
+    sides: u32,
+    state: u64,
+}
+
+/// Rolls the numbers of a script, over and over.
+pub(crate) struct ScriptedDie {
+    script: Vec<u32>,
+    next: usize,
+}
+
+impl DeterministicDie {
+    pub(crate) fn new(sides: u32) -> DeterministicDie {
+        DeterministicDie { sides, next: 1 }
+    }
+}
+
+impl SeededDie {
+    pub(crate) fn new(sides: u32, seed: u64) -> SeededDie {
+        // xorshift never leaves a state of zero
+        let state = seed ^ 0x9E37_79B9_7F4A_7C15;
+        SeededDie {
+            sides,
+            state: if state == 0 { 1 } else { state },
+        }
+    }
+}
+
+impl ScriptedDie {
+    pub(crate) fn new(script: Vec<u32>) -> ScriptedDie {
+        assert!(!script.is_empty(), "a script of at least one roll");
+        ScriptedDie { script, next: 0 }
+    }
 }
 
-impl Die {
+impl Die for DeterministicDie {
     fn roll(&mut self) -> u32 {
         let res = self.next;
-        self.next = 1 + (res % 1000);
-        self.rolls += 1;
+        self.next = res % self.sides + 1;
         res
     }
 }
 
+impl Die for SeededDie {
+    fn roll(&mut self) -> u32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state % self.sides as u64) as u32 + 1
+    }
+}
+
+impl Die for ScriptedDie {
+    fn roll(&mut self) -> u32 {
+        let res = self.script[self.next];
+        self.next = (self.next + 1) % self.script.len();
+        res
+    }
+}
+
+#[derive(Clone)]
+struct Player {
+    pos: u32,
+    score: u32,
+}
+
+/// One player's turn: what they rolled and where it took them.
+struct Turn {
+    player: usize,
+    rolls: Vec<u32>,
+    pos: u32,
+    score: u32,
+}
+
+/// How a game ended.
+struct Outcome {
+    winner: usize,
+    scores: Vec<u32>,
+    rolls: u32,
+}
+
+struct Game {
+    rules: DiracGame,
+    players: Vec<Player>,
+    die: Box<dyn Die>,
+    rolls: u32,
+    log: Vec<Turn>,
+}
+
 impl Player {
-    fn mov(&mut self, dist: u32) {
-        let n = (self.pos + dist - 1) % 10 + 1;
+    fn mov(&mut self, dist: u32, board: u32) {
+        let n = (self.pos + dist - 1) % board + 1;
         self.pos = n;
         self.score += n;
     }
 }
 
 impl Game {
-    fn create(pos1: u32, pos2: u32) -> Game {
+    fn new(rules: DiracGame, positions: &[u32], die: Box<dyn Die>) -> Game {
         Game {
-            p1: Player {
-                pos: pos1,
-                score: 0,
-            },
-            p2: Player {
-                pos: pos2,
-                score: 0,
-            },
-            die: Die { next: 1, rolls: 0 },
+            rules,
+            players: positions
+                .iter()
+                .map(|&pos| Player { pos, score: 0 })
+                .collect(),
+            die,
+            rolls: 0,
+            log: vec![],
         }
     }
 
-    fn mov(&mut self) -> u32 {
-        let res: u32;
+    /// Takes turns until a player reaches the target.
+    fn play(&mut self) -> Outcome {
+        let mut player = 0;
         loop {
-            let dist1 = self.die.roll() + self.die.roll() + self.die.roll();
-            self.p1.mov(dist1);
-            if (self.p1.score >= 1000) {
-                res = self.p2.score * self.die.rolls;
-                break;
-            }
-            let dist2 = self.die.roll() + self.die.roll() + self.die.roll();
-            self.p2.mov(dist2);
-            if (self.p2.score >= 1000) {
-                res = self.p1.score * self.die.rolls;
-                break;
+            let rolls: Vec<u32> = (0..self.rules.rolls).map(|_| self.die.roll()).collect();
+            self.rolls += rolls.len() as u32;
+            let p = &mut self.players[player];
+            p.mov(rolls.iter().sum(), self.rules.board);
+            self.log.push(Turn {
+                player,
+                rolls,
+                pos: p.pos,
+                score: p.score,
+            });
+            if p.score >= self.rules.target {
+                return Outcome {
+                    winner: player,
+                    scores: self.players.iter().map(|p| p.score).collect(),
+                    rolls: self.rolls,
+                };
             }
+            player = (player + 1) % self.players.len();
         }
-        res
+    }
+}
+
+impl Outcome {
+    /// The lowest score of a losing player times the number of rolls.
+    fn answer(&self) -> u32 {
+        self.losing_score() * self.rolls
+    }
+
+    fn losing_score(&self) -> u32 {
+        self.scores
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != self.winner)
+            .map(|(_, &score)| score)
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+/// "Player 1 rolls 1+2+3 and moves to space 10 for a total score of 10."
+impl fmt::Display for Turn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rolls: Vec<String> = self.rolls.iter().map(|r| r.to_string()).collect();
+        write!(
+            f,
+            "Player {} rolls {} and moves to space {} for a total score of {}.",
+            self.player + 1,
+            rolls.join("+"),
+            self.pos,
+            self.score
+        )
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Player {} wins after {} rolls, the losing score is {}: {}",
+            self.winner + 1,
+            self.rolls,
+            self.losing_score(),
+            self.answer()
+        )
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::aoc::answers;
+
+    fn solve_input(input: &str) -> Result<u32, ParseError> {
+        Ok(solve_positions(read_positions(
+            input,
+            &DiracGame::PRACTICE,
+        )?))
+    }
+
+    const SAMPLE: &str = "Player 1 starting position: 4\nPlayer 2 starting position: 8";
 
     #[test]
     fn result() {
-        let mut game = Game::create(10, 6);
-        let result = game.mov();
-        assert_eq!(result, 900099);
+        answers::check(&Day21a);
+    }
+
+    #[test]
+    fn result_sample() {
+        assert_eq!(solve_input(SAMPLE).unwrap(), 739785);
+    }
+
+    #[test]
+    fn turn_log() {
+        let log = practice(SAMPLE, Box::new(DeterministicDie::new(100))).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(
+            lines[..2],
+            [
+                "Player 1 rolls 1+2+3 and moves to space 10 for a total score of 10.",
+                "Player 2 rolls 4+5+6 and moves to space 3 for a total score of 3.",
+            ]
+        );
+        assert_eq!(
+            lines[lines.len() - 1],
+            "Player 1 wins after 993 rolls, the losing score is 745: 739785"
+        );
+    }
+
+    #[test]
+    fn dice() {
+        let mut die = DeterministicDie::new(3);
+        let rolls: Vec<u32> = (0..5).map(|_| die.roll()).collect();
+        assert_eq!(rolls, [1, 2, 3, 1, 2]);
+        let mut die = ScriptedDie::new(vec![6, 1]);
+        let rolls: Vec<u32> = (0..3).map(|_| die.roll()).collect();
+        assert_eq!(rolls, [6, 1, 6]);
+        let (mut a, mut b) = (SeededDie::new(6, 7), SeededDie::new(6, 7));
+        for _ in 0..100 {
+            let roll = a.roll();
+            assert!((1..=6).contains(&roll));
+            assert_eq!(roll, b.roll());
+        }
+    }
+
+    #[test]
+    fn more_players() {
+        let rules = DiracGame {
+            players: 3,
+            ..DiracGame::PRACTICE
+        };
+        // every turn moves 3 spaces
+        let die = ScriptedDie::new(vec![1]);
+        let mut game = Game::new(rules, &[1, 2, 3], Box::new(die));
+        let outcome = game.play();
+        assert_eq!(game.log[2].player, 2);
+        assert_eq!(outcome.rolls, 3 * game.log.len() as u32);
+        assert!(outcome.scores[outcome.winner] >= 1000);
+    }
+
+    #[test]
+    fn practice_players() {
+        let input = "Player 1 starting position: 1\n\
+                     Player 2 starting position: 2\n\
+                     Player 3 starting position: 3";
+        let log = practice(input, Box::new(ScriptedDie::new(vec![1]))).unwrap();
+        assert!(log.contains("\nPlayer 3 rolls 1+1+1 and moves to space 6"));
+        let input = "Player 1 starting position: 10";
+        let log = practice(input, Box::new(ScriptedDie::new(vec![1]))).unwrap();
+        assert!(log.ends_with("Player 1 wins after 549 rolls, the losing score is 0: 0\n"));
+        let e = practice("", Box::new(DeterministicDie::new(100))).unwrap_err();
+        assert_eq!(e.to_string(), "1:1 expected 1 player");
     }
 }
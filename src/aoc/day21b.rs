@@ -1,127 +1,285 @@
 use std::collections::HashMap;
 
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
+
+pub(crate) struct Day21b;
+
+impl Solution for Day21b {
+    fn day(&self) -> u32 {
+        21
+    }
+
+    fn part(&self) -> char {
+        'b'
+    }
+
+    fn title(&self) -> &'static str {
+        "Dirac Dice"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_positions(input, &DiracGame::PUZZLE)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_positions(solution::unbox(parsed)).into())
+    }
+}
+
+fn solve_positions(positions: Vec<u32>) -> u64 {
+    let game = DiracGame::PUZZLE;
+    game.wins(&positions).into_iter().max().unwrap()
+}
+
+pub(crate) fn read_positions(input: &str, game: &DiracGame) -> Result<Vec<u32>, ParseError> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"^Player (\d+) starting position: (\d+)$").unwrap();
+    }
+    let mut positions = vec![];
+    for (no, line) in parse::lines(input) {
+        let m = RE
+            .captures(line)
+            .ok_or_else(|| ParseError::new(no, 1, "'Player <n> starting position: <pos>'"))?;
+        if positions.len() == game.players {
+            return Err(ParseError::new(no, 1, &players(game)));
+        }
+        // the players take turns in the order they are numbered
+        let player: usize = parse::capture(&m, 1).map_err(|e| e.on_line(no))?;
+        if player != positions.len() + 1 {
+            return Err(ParseError::new(
+                no,
+                m.get(1).unwrap().start() + 1,
+                &format!("player {}", positions.len() + 1),
+            ));
+        }
+        let pos: u32 = parse::capture(&m, 2).map_err(|e| e.on_line(no))?;
+        if !(1..=game.board).contains(&pos) {
+            return Err(ParseError::new(
+                no,
+                m.get(2).unwrap().start() + 1,
+                &format!("a position 1..{}", game.board),
+            ));
+        }
+        positions.push(pos);
+    }
+    if positions.len() != game.players {
+        return Err(ParseError::new(positions.len() + 1, 1, &players(game)));
+    }
+    Ok(positions)
+}
+
+/// "2 players", as many as `game` is for.
+fn players(game: &DiracGame) -> String {
+    match game.players {
+        1 => "1 player".to_string(),
+        n => format!("{} players", n),
+    }
+}
+
+/// The rules of a game of Dirac Dice. Each turn a player rolls the die
+/// `rolls` times and moves forward by the total, around a board of spaces
+/// 1 to `board`; the space they land on adds to their score. The first to
+/// reach `target` wins.
+///
+/// The Dirac die splits the universe on every roll; the practice game uses
+/// an ordinary die.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct DiracGame {
+    pub(crate) board: u32,
+    pub(crate) target: u32,
+    /// The die shows 1 to `sides`.
+    pub(crate) sides: u32,
+    pub(crate) rolls: u32,
+    pub(crate) players: usize,
+}
+
+impl DiracGame {
+    pub(crate) const PUZZLE: DiracGame = DiracGame {
+        board: 10,
+        target: 21,
+        sides: 3,
+        rolls: 3,
+        players: 2,
+    };
+
+    pub(crate) const PRACTICE: DiracGame = DiracGame {
+        board: 10,
+        target: 1000,
+        sides: 100,
+        rolls: 3,
+        players: 2,
+    };
+
+    /// In how many universes each player wins, with the players starting at
+    /// `positions` and taking turns in that order.
+    pub(crate) fn wins(&self, positions: &[u32]) -> Vec<u64> {
+        assert_eq!(positions.len(), self.players, "a position for each player");
+        let initial = GameConfiguration {
+            players: positions
+                .iter()
+                .map(|&pos| PlayerConfiguration { pos, score: 0 })
+                .collect(),
+            turn: 0,
+        };
+        let mut solver = Solver {
+            game: self,
+            outcomes: self.outcomes(),
+            known: HashMap::new(),
+        };
+        solver.count_wins(&initial)
+    }
+
+    /// Every total of one turn's rolls, and in how many universes it comes up.
+    fn outcomes(&self) -> Vec<(u32, u64)> {
+        let mut counts: Vec<u64> = vec![1];
+        for _ in 0..self.rolls {
+            let mut next = vec![0; counts.len() + self.sides as usize];
+            for (total, &n) in counts.iter().enumerate() {
+                for face in 1..=self.sides as usize {
+                    next[total + face] += n;
+                }
+            }
+            counts = next;
+        }
+        (0..).zip(counts).filter(|&(_, n)| n > 0).collect()
+    }
+}
+
 #[derive(Eq, PartialEq, Hash, Clone)]
 struct PlayerConfiguration {
     pos: u32,
     score: u32,
 }
 
+/// Where the game stands: every player, and whose turn it is.
 #[derive(Eq, PartialEq, Hash, Clone)]
 struct GameConfiguration {
-    p1: PlayerConfiguration,
-    p2: PlayerConfiguration,
-}
-
-struct Game {
-    configs: HashMap<PlayerConfiguration, u32>,
+    players: Vec<PlayerConfiguration>,
+    turn: usize,
 }
 
 impl PlayerConfiguration {
-    fn mov(&self, dist: u32) -> PlayerConfiguration {
-        let n = (self.pos + dist - 1) % 10 + 1;
+    fn mov(&self, dist: u32, board: u32) -> PlayerConfiguration {
+        let n = (self.pos + dist - 1) % board + 1;
         PlayerConfiguration {
             pos: n,
             score: self.score + n,
         }
     }
-
-    fn is_final(&self) -> bool {
-        self.score >= 21
-    }
 }
 
-impl GameConfiguration {
-    fn is_final(&self) -> bool {
-        self.p1.is_final() || self.p2.is_final()
-    }
+/// Counts wins for every configuration reached, each one only once.
+struct Solver<'a> {
+    game: &'a DiracGame,
+    outcomes: Vec<(u32, u64)>,
+    known: HashMap<GameConfiguration, Vec<u64>>,
+}
 
-    fn winner(&self) -> u32 {
-        if self.p1.is_final() {
-            1
-        } else {
-            2
+impl Solver<'_> {
+    fn count_wins(&mut self, config: &GameConfiguration) -> Vec<u64> {
+        if let Some(wins) = self.known.get(config) {
+            return wins.clone();
+        }
+        let turn = config.turn;
+        let mut wins = vec![0; self.game.players];
+        for i in 0..self.outcomes.len() {
+            let (dist, universes) = self.outcomes[i];
+            let moved = config.players[turn].mov(dist, self.game.board);
+            if moved.score >= self.game.target {
+                wins[turn] += universes;
+                continue;
+            }
+            let mut next = GameConfiguration {
+                players: config.players.clone(),
+                turn: (turn + 1) % self.game.players,
+            };
+            next.players[turn] = moved;
+            for (w, n) in wins.iter_mut().zip(self.count_wins(&next)) {
+                *w += universes * n;
+            }
         }
+        self.known.insert(config.clone(), wins.clone());
+        wins
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use std::cmp::max;
+    use crate::aoc::answers;
+
+    fn solve_input(input: &str) -> Result<u64, ParseError> {
+        Ok(solve_positions(read_positions(input, &DiracGame::PUZZLE)?))
+    }
 
     #[test]
     fn result() {
-        let initial = GameConfiguration {
-            p1: PlayerConfiguration { pos: 10, score: 0 },
-            p2: PlayerConfiguration { pos: 6, score: 0 },
-        };
+        answers::check(&Day21b);
+    }
 
-        let mut config = HashMap::new();
-        let mut p1wins = 0u64;
-        let mut p2wins = 0u64;
-
-        config.insert(initial, 1);
-
-        for mve in 0..21 {
-            let mut new_config = HashMap::new();
-            for (key, val) in config {
-                if !key.is_final() {
-                    for d11 in 1..4 {
-                        for d12 in 1..4 {
-                            for d13 in 1..4 {
-                                let d1 = d11 + d12 + d13;
-                                let new1 = key.p1.mov(d1);
-                                if new1.is_final() {
-                                    let newgc = GameConfiguration {
-                                        p1: new1,
-                                        p2: key.p2.clone(),
-                                    };
-                                    let count_o = new_config.get(&newgc);
-                                    let count = match count_o {
-                                        None => val,
-                                        Some(x) => x + val,
-                                    };
-                                    new_config.insert(newgc, count);
-                                } else {
-                                    for d21 in 1..4 {
-                                        for d22 in 1..4 {
-                                            for d23 in 1..4 {
-                                                let d2 = d21 + d22 + d23;
-                                                let new2 = key.p2.mov(d2);
-                                                let newgc = GameConfiguration {
-                                                    p1: new1.clone(),
-                                                    p2: new2,
-                                                };
-                                                let count_o = new_config.get(&newgc);
-                                                let count = match count_o {
-                                                    None => val,
-                                                    Some(x) => x + val,
-                                                };
-                                                new_config.insert(newgc, count);
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+    #[test]
+    fn result_sample() {
+        let input = "Player 1 starting position: 4\nPlayer 2 starting position: 8";
+        assert_eq!(solve_input(input).unwrap(), 444356092776315);
+        let wins = DiracGame::PUZZLE.wins(&[4, 8]);
+        assert_eq!(wins, [444356092776315, 341960390180808]);
+    }
 
-            for (k, v) in &new_config {
-                if (k.is_final()) {
-                    if (k.winner() == 1) {
-                        p1wins += v;
-                    } else {
-                        p2wins += v;
-                    }
-                }
-            }
-            config = new_config;
-        }
+    #[test]
+    fn outcomes() {
+        let outcomes = DiracGame::PUZZLE.outcomes();
+        assert_eq!(
+            outcomes,
+            [(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)]
+        );
+    }
 
-        assert_eq!(max(p1wins, p2wins), 306719685234774)
+    #[test]
+    fn other_games() {
+        // a die with one side plays a single game, the third player wins
+        let game = DiracGame {
+            board: 10,
+            target: 4,
+            sides: 1,
+            rolls: 1,
+            players: 3,
+        };
+        assert_eq!(game.wins(&[1, 2, 3]), [0, 0, 1]);
+        // two coin flips a turn on a board of four
+        let game = DiracGame {
+            board: 4,
+            target: 5,
+            sides: 2,
+            rolls: 2,
+            players: 2,
+        };
+        assert_eq!(game.wins(&[1, 1]), [92, 53]);
+    }
 
-        //assert_eq!(result, 900099);
+    #[test]
+    fn bad_positions() {
+        let e = read_positions("Player 1 starting position: 11", &DiracGame::PUZZLE).unwrap_err();
+        assert_eq!(e.to_string(), "1:29 expected a position 1..10");
+        let e = read_positions("Player 1 starting position: 1", &DiracGame::PUZZLE).unwrap_err();
+        assert_eq!(e.to_string(), "2:1 expected 2 players");
+    }
+
+    #[test]
+    fn bad_players() {
+        let input = "Player 2 starting position: 4\nPlayer 1 starting position: 8";
+        let e = read_positions(input, &DiracGame::PUZZLE).unwrap_err();
+        assert_eq!(e.to_string(), "1:8 expected player 1");
+        let input = "Player 1 starting position: 4\nPlayer 7 starting position: 8";
+        let e = read_positions(input, &DiracGame::PUZZLE).unwrap_err();
+        assert_eq!(e.to_string(), "2:8 expected player 2");
+        let input = "Player 1 starting position: 4\n\
+                     Player 2 starting position: 8\n\
+                     Player 3 starting position: 1";
+        let e = read_positions(input, &DiracGame::PUZZLE).unwrap_err();
+        assert_eq!(e.to_string(), "3:1 expected 2 players");
     }
 }
@@ -1,168 +1,525 @@
-use crate::aoc::file;
-use std::cmp::min;
-use std::collections::HashSet;
-use std::path::Path;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
 
-#[derive(Eq, PartialEq, Hash, Clone)]
-struct Board {
-    board: Vec<Vec<char>>,
+use crate::aoc::parse::{self, ParseError};
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
+
+pub(crate) struct Day23a;
+
+impl Solution for Day23a {
+    fn day(&self) -> u32 {
+        23
+    }
+
+    fn part(&self) -> char {
+        'a'
+    }
+
+    fn title(&self) -> &'static str {
+        "Amphipod"
+    }
+
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_input(input)?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_burrow(solution::unbox(parsed))?.into())
+    }
+}
+
+pub(crate) fn solve_burrow(burrow: Burrow) -> Result<u32, SolveError> {
+    let moves = organize(burrow).ok_or_else(unorganized)?;
+    Ok(moves.iter().map(|m| m.cost).sum())
 }
 
-#[derive(Eq, PartialEq, Hash, Clone)]
-struct Pos {
-    x: usize,
-    y: usize,
+fn unorganized() -> SolveError {
+    SolveError::NoAnswer("the amphipods cannot organize this burrow".to_string())
 }
 
-struct Move {
-    from: Pos,
-    to: Pos,
+/// An empty space and the amphipods as the diagram shows them.
+const AMPHIPODS: [char; 5] = ['.', 'A', 'B', 'C', 'D'];
+
+/// Energy it takes an amphipod of type A, B, C or D to take a step.
+const ENERGY: [u32; 4] = [1, 10, 100, 1000];
+
+/// Spaces in the hallway.
+const HALLWAY: usize = 11;
+
+/// The hallway spaces right outside the rooms of A, B, C and D. Amphipods
+/// never stop there.
+const DOORS: [usize; 4] = [2, 4, 6, 8];
+
+/// Rooms as deep as fit in the three bits per space of a `Burrow`.
+const MAX_DEPTH: usize = 7;
+
+/// Where every amphipod is, three bits for each space: 0 for an empty one,
+/// 1 to 4 for an amphipod of type A to D. The hallway comes first, from left
+/// to right, then the rooms, each from the top down.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub(crate) struct Burrow {
+    spaces: u128,
+    depth: usize,
+}
+
+/// An amphipod going from one space to another in one go.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Move {
+    from: usize,
+    to: usize,
     cost: u32,
 }
 
-const HOME_A: usize = 3;
-const HOME_B: usize = 5;
-const HOME_C: usize = 7;
-const HOME_D: usize = 9;
-const HOME_Y1: usize = 2;
-const HOME_Y2: usize = 3;
-
-impl Board {
-    fn get_moves(&self) -> Vec<Move> {
-        let mut moves: Vec<Move> = vec![];
-        for y in 1..3 {
-            let line = &self.board[y];
-            for x in 1..line.len() - 1 {
-                let c = line[x];
-                match c {
-                    'A' => self.add_moves(&mut moves, Pos { x: x, y: y }, 1, HOME_A),
-                    'B' => self.add_moves(&mut moves, Pos { x: x, y: y }, 10, 5),
-                    'C' => self.add_moves(&mut moves, Pos { x: x, y: y }, 100, 7),
-                    'D' => self.add_moves(&mut moves, Pos { x: x, y: y }, 1000, 9),
-                    _ => {}
-                }
+impl Burrow {
+    /// Everyone in their own room.
+    fn organized(depth: usize) -> Burrow {
+        let mut b = Burrow { spaces: 0, depth };
+        for room in 0..4 {
+            for slot in 0..depth {
+                b.set(room_space(room, slot), room as u8 + 1);
             }
         }
-        moves
+        b
+    }
+
+    /// The burrow as part two has it: the two lines folded away in the
+    /// diagram put back in below the top of the rooms.
+    ///
+    /// ```text
+    ///   #D#C#B#A#
+    ///   #D#B#A#C#
+    /// ```
+    pub(crate) fn unfold(&self) -> Result<Burrow, ParseError> {
+        const FOLDED: [[u8; 4]; 2] = [[4, 3, 2, 1], [4, 2, 1, 3]];
+        if self.depth + FOLDED.len() > MAX_DEPTH {
+            let expected = format!("rooms at most {} deep", MAX_DEPTH - FOLDED.len());
+            return Err(ParseError::new(3 + MAX_DEPTH - FOLDED.len(), 1, &expected));
+        }
+        let mut b = Burrow {
+            spaces: 0,
+            depth: self.depth + FOLDED.len(),
+        };
+        for space in 0..HALLWAY {
+            b.set(space, self.get(space));
+        }
+        for room in 0..4 {
+            b.set(room_space(room, 0), self.get(room_space(room, 0)));
+            for (i, line) in FOLDED.iter().enumerate() {
+                b.set(room_space(room, 1 + i), line[room]);
+            }
+            for slot in 1..self.depth {
+                let amphipod = self.get(room_space(room, slot));
+                b.set(room_space(room, slot + FOLDED.len()), amphipod);
+            }
+        }
+        Ok(b)
     }
 
-    fn add_moves(&self, moves: &mut Vec<Move>, pos: Pos, cost: u32, homex: usize) {
-        let neighbours = pos.neighbours();
-        for n in neighbours {
-            if self.is_free(&n) && !self.is_foreign_home_move(&pos, &n, homex) {
-                moves.push(Move {
-                    from: pos.clone(),
-                    to: n,
-                    cost: cost,
-                });
+    fn get(&self, space: usize) -> u8 {
+        (self.spaces >> (3 * space) & 7) as u8
+    }
+
+    fn set(&mut self, space: usize, amphipod: u8) {
+        self.spaces = self.spaces & !(7 << (3 * space)) | (amphipod as u128) << (3 * space);
+    }
+
+    /// Every move allowed from here: out of a room to where the hallway is
+    /// free to stop, or into an amphipod's own room, but only once no other
+    /// types are left in it.
+    fn moves(&self) -> Vec<Move> {
+        let mut moves = vec![];
+        for from in 0..HALLWAY {
+            let amphipod = self.get(from);
+            if amphipod != 0 {
+                moves.extend(self.move_home(from, amphipod));
+            }
+        }
+        for room in 0..4 {
+            let slot = match (0..self.depth).find(|&s| self.get(room_space(room, s)) != 0) {
+                Some(slot) => slot,
+                None => continue,
+            };
+            if self.settled(room, slot) {
+                continue;
+            }
+            let from = room_space(room, slot);
+            let amphipod = self.get(from);
+            moves.extend(self.move_home(from, amphipod));
+            for to in (0..HALLWAY).filter(|h| !DOORS.contains(h)) {
+                if self.way_free(from, to) {
+                    moves.push(Move {
+                        from,
+                        to,
+                        cost: distance(from, to) * ENERGY[amphipod as usize - 1],
+                    });
+                }
             }
         }
+        moves
     }
 
-    fn is_free(&self, pos: &Pos) -> bool {
-        self.board[pos.y][pos.x] == '.'
+    /// The move from `from` to the bottom of the room of `amphipod`, if it
+    /// can go there.
+    fn move_home(&self, from: usize, amphipod: u8) -> Option<Move> {
+        let room = amphipod as usize - 1;
+        let mut occupants = (0..self.depth).map(|s| self.get(room_space(room, s)));
+        if occupants.any(|a| a != 0 && a != amphipod) {
+            return None;
+        }
+        let slot = (0..self.depth)
+            .rev()
+            .find(|&s| self.get(room_space(room, s)) == 0)?;
+        let to = room_space(room, slot);
+        if room_of(from) == Some(room) || !self.way_free(from, to) {
+            return None;
+        }
+        Some(Move {
+            from,
+            to,
+            cost: distance(from, to) * ENERGY[room],
+        })
     }
-    fn is_foreign_home_move(&self, from: &Pos, to: &Pos, homex: usize) -> bool {
-        to.y > from.y && to.x != homex
+
+    /// Whether the amphipod in `slot` of `room` is home for good: it and
+    /// everyone below belong there.
+    fn settled(&self, room: usize, slot: usize) -> bool {
+        (slot..self.depth).all(|s| self.get(room_space(room, s)) as usize == room + 1)
     }
 
-    fn is_complete(&self) -> bool {
-        /*        self.board[HOME_Y1][HOME_A] == 'A'
-        && self.board[HOME_Y2][HOME_A] == 'A'
-        && self.board[HOME_Y1][HOME_B] == 'B'
-        && self.board[HOME_Y2][HOME_B] == 'B'
-        && self.board[HOME_Y1][HOME_C] == 'C'
-        && self.board[HOME_Y2][HOME_C] == 'C'
-        && */
-        self.board[HOME_Y1][HOME_D] == 'D' && self.board[HOME_Y2][HOME_D] == 'D'
+    /// Whether the spaces on the way from `from` to `to` are empty, `from`
+    /// itself aside.
+    fn way_free(&self, from: usize, to: usize) -> bool {
+        let mut rest = *self;
+        rest.set(from, 0);
+        let (x1, x2) = (column(from), column(to));
+        let hallway = x1.min(x2)..=x1.max(x2);
+        let up = room_of(from).map(|r| (r, slot_of(from)));
+        let down = room_of(to).map(|r| (r, slot_of(to)));
+        hallway.into_iter().all(|h| rest.get(h) == 0)
+            && [up, down]
+                .into_iter()
+                .flatten()
+                .all(|(room, slot)| (0..=slot).all(|s| rest.get(room_space(room, s)) == 0))
     }
 
-    fn moove(&self, m: &Move) -> Board {
-        let mut b = self.clone();
-        b.board[m.to.y][m.to.x] = self.board[m.from.y][m.from.x];
-        b.board[m.from.y][m.from.x] = '.';
+    fn apply(&self, m: &Move) -> Burrow {
+        let mut b = *self;
+        b.set(m.to, self.get(m.from));
+        b.set(m.from, 0);
         b
     }
 }
 
-impl Pos {
-    fn neighbours(&self) -> Vec<Pos> {
-        vec![
-            Pos {
-                x: self.x - 1,
-                y: self.y,
-            },
-            Pos {
-                x: self.x + 1,
-                y: self.y,
-            },
-            Pos {
-                x: self.x,
-                y: self.y - 1,
-            },
-            Pos {
-                x: self.x,
-                y: self.y + 1,
-            },
-        ]
-    }
-}
-
-fn solve(b: &Board) -> u32 {
-    let mut path: HashSet<Board> = HashSet::new();
-    solve_1(&b, &mut path, 0, 20000)
-}
-
-fn solve_1(b: &Board, path: &mut HashSet<Board>, cost: u32, mincost: u32) -> u32 {
-    if b.is_complete() {
-        cost
-    } else {
-        let mut movs = b.get_moves();
-        if movs.is_empty() {
-            u32::MAX
-        } else {
-            let mut mincost_here = mincost;
-            let mut cost_here = u32::MAX;
-            movs.sort_by(|a, b| b.cost.cmp(&a.cost));
-            for m in movs {
-                if m.cost + cost < mincost {
-                    let next = b.moove(&m);
-                    if path.insert(next.clone()) {
-                        let c = solve_1(&next, path, cost + m.cost, mincost_here);
-                        cost_here = min(cost_here, c);
-                        mincost_here = min(mincost_here, c);
-                        path.remove(&next);
-                    } else {
-                        println!("duplicate");
-                    }
+fn room_space(room: usize, slot: usize) -> usize {
+    HALLWAY + room * MAX_DEPTH + slot
+}
+
+fn room_of(space: usize) -> Option<usize> {
+    space.checked_sub(HALLWAY).map(|s| s / MAX_DEPTH)
+}
+
+/// How far down a room `space` is, 0 for the hallway.
+fn slot_of(space: usize) -> usize {
+    space.checked_sub(HALLWAY).map_or(0, |s| s % MAX_DEPTH)
+}
+
+/// The hallway space `space` is at or below.
+fn column(space: usize) -> usize {
+    room_of(space).map_or(space, |r| DOORS[r])
+}
+
+/// Steps from `from` to `to`, by way of the hallway unless both are in it.
+fn distance(from: usize, to: usize) -> u32 {
+    let down = |space: usize| room_of(space).map_or(0, |_| slot_of(space) + 1);
+    (column(from).abs_diff(column(to)) + down(from) + down(to)) as u32
+}
+
+/// The moves that organize the amphipods with the least energy, if they can
+/// be.
+pub(crate) fn organize(start: Burrow) -> Option<Vec<Move>> {
+    let goal = Burrow::organized(start.depth);
+    let mut best: Reached = HashMap::new();
+    let mut queue = BinaryHeap::new();
+    best.insert(start, (0, None));
+    queue.push(Reverse((0, start)));
+    while let Some(Reverse((energy, b))) = queue.pop() {
+        if b == goal {
+            return Some(moves_to(&best, goal));
+        }
+        if energy > best[&b].0 {
+            // already reached with less energy
+            continue;
+        }
+        for m in b.moves() {
+            let next = b.apply(&m);
+            let e = energy + m.cost;
+            if best.get(&next).is_none_or(|&(known, _)| e < known) {
+                best.insert(next, (e, Some((b, m))));
+                queue.push(Reverse((e, next)));
+            }
+        }
+    }
+    None
+}
+
+/// The least energy found to get somewhere, and the move that got there.
+type Reached = HashMap<Burrow, (u32, Option<(Burrow, Move)>)>;
+
+fn moves_to(best: &Reached, goal: Burrow) -> Vec<Move> {
+    let mut moves = vec![];
+    let mut b = goal;
+    while let Some((before, m)) = best[&b].1 {
+        moves.push(m);
+        b = before;
+    }
+    moves.reverse();
+    moves
+}
+
+/// The burrow after every move of the cheapest plan, each with the energy
+/// spent so far, to check it against the puzzle:
+///
+/// ```text
+/// #############
+/// #...........#
+/// ###B#C#B#D###
+///   #A#D#C#A#
+///   #########
+///
+/// B moves for 40, 40 in all:
+/// #############
+/// #...B.......#
+/// ...
+/// ```
+pub(crate) fn replay(input: &str, unfold: bool) -> Result<String, SolveError> {
+    let mut b = read_input(input)?;
+    if unfold {
+        b = b.unfold()?;
+    }
+    let moves = organize(b).ok_or_else(unorganized)?;
+    let mut s = format!("{}", b);
+    let mut energy = 0;
+    for m in moves {
+        let amphipod = AMPHIPODS[b.get(m.from) as usize];
+        b = b.apply(&m);
+        energy += m.cost;
+        s += &format!(
+            "\n{} moves for {}, {} in all:\n{}",
+            amphipod, m.cost, energy, b
+        );
+    }
+    Ok(s)
+}
+
+/// The diagram of the burrow: the hallway, the rooms from the top down, and
+/// the wall below. Trailing blanks are ignored.
+pub(crate) fn read_input(input: &str) -> Result<Burrow, ParseError> {
+    let lines: Vec<(usize, &str)> = parse::lines(input)
+        .map(|(no, line)| (no, line.trim_end()))
+        .collect();
+    let depth = lines.len().saturating_sub(3);
+    if depth == 0 {
+        return Err(ParseError::new(lines.len() + 1, 1, "a room"));
+    }
+    if depth > MAX_DEPTH {
+        let expected = format!("rooms at most {} deep", MAX_DEPTH);
+        return Err(ParseError::new(3 + MAX_DEPTH, 1, &expected));
+    }
+    let mut b = Burrow { spaces: 0, depth };
+    for (i, &(no, line)) in lines.iter().enumerate() {
+        // '.' where a space is
+        let (template, spaces): (&str, Vec<usize>) = match i {
+            0 => ("#############", vec![]),
+            1 => ("#...........#", (0..HALLWAY).collect()),
+            2 => ("###.#.#.#.###", (0..4).map(|r| room_space(r, 0)).collect()),
+            _ if i == lines.len() - 1 => ("  #########", vec![]),
+            _ => (
+                "  #.#.#.#.#",
+                (0..4).map(|r| room_space(r, i - 2)).collect(),
+            ),
+        };
+        read_line(&mut b, line, template, &spaces).map_err(|e| e.on_line(no))?;
+    }
+    for amphipod in 1..=4 {
+        let count = (0..room_space(4, 0))
+            .filter(|&s| b.get(s) == amphipod)
+            .count();
+        if count != depth {
+            let expected = format!("{} amphipods of each type", depth);
+            return Err(ParseError::new(1, 1, &expected));
+        }
+    }
+    Ok(b)
+}
+
+/// Fills `spaces` from the '.' in `template` that `line` has to match.
+fn read_line(
+    b: &mut Burrow,
+    line: &str,
+    template: &str,
+    spaces: &[usize],
+) -> Result<(), ParseError> {
+    let mut spaces = spaces.iter();
+    let mut chars = line.chars();
+    for (i, t) in template.chars().enumerate() {
+        let c = chars.next();
+        if t == '.' {
+            let space = *spaces.next().unwrap();
+            match c {
+                Some('.') => {}
+                Some(c @ 'A'..='D') => {
+                    let amphipod = AMPHIPODS.iter().position(|&a| a == c).unwrap();
+                    b.set(space, amphipod as u8);
                 }
+                _ => return Err(ParseError::at(i + 1, "'.' or an amphipod 'A' to 'D'")),
             }
-            cost_here
+        } else if c != Some(t) {
+            return Err(ParseError::at(i + 1, &format!("'{}'", t)));
         }
     }
+    match chars.next() {
+        None => Ok(()),
+        Some(_) => Err(ParseError::at(template.len() + 1, "end of line")),
+    }
 }
 
-fn read_input(f: &Path) -> Board {
-    let lines = file::read_lines(f).unwrap();
-    let mut b = Vec::new();
-    for line in lines {
-        let line = line.unwrap();
-        b.push(line.chars().collect());
+/// The diagram, as `read_input` reads it.
+impl fmt::Display for Burrow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let show = |space: usize| AMPHIPODS[self.get(space) as usize];
+        let rooms = |slot: usize| -> String {
+            let cells: Vec<String> = (0..4)
+                .map(|r| show(room_space(r, slot)).to_string())
+                .collect();
+            cells.join("#")
+        };
+        writeln!(f, "#############")?;
+        writeln!(f, "#{}#", (0..HALLWAY).map(show).collect::<String>())?;
+        writeln!(f, "###{}###", rooms(0))?;
+        for slot in 1..self.depth {
+            writeln!(f, "  #{}#", rooms(slot))?;
+        }
+        writeln!(f, "  #########")
     }
-    Board { board: b }
 }
 
+/// The example of the puzzle.
 #[cfg(test)]
-mod test {
+pub(crate) const SAMPLE: &str = "\
+#############
+#...........#
+###B#C#B#D###
+  #A#D#C#A#
+  #########";
 
+#[cfg(test)]
+mod test {
     use super::*;
+    use crate::aoc::answers;
+
+    fn solve_input(input: &str) -> Result<u32, SolveError> {
+        solve_burrow(read_input(input)?)
+    }
 
     #[test]
     fn result() {
-        let b = read_input(&file::input("input23.txt"));
-        let result = solve(&b);
-        assert_eq!(result, 1);
+        answers::check(&Day23a);
+    }
+
+    #[test]
+    fn result_sample() {
+        assert_eq!(solve_input(SAMPLE).unwrap(), 12521);
+    }
+
+    #[test]
+    fn organized() {
+        let b = read_input("#############\n#...........#\n###A#B#C#D###\n  #A#B#C#D#\n  #########")
+            .unwrap();
+        assert_eq!(b, Burrow::organized(2));
+        assert!(b.moves().is_empty());
+        assert_eq!(organize(b), Some(vec![]));
+    }
+
+    #[test]
+    fn moves() {
+        let b = read_input("#############\n#.........A.#\n###.#B#C#D###\n  #A#B#C#D#\n  #########")
+            .unwrap();
+        // straight home, nothing else
+        let moves = b.moves();
+        assert_eq!(
+            moves,
+            [Move {
+                from: 9,
+                to: room_space(0, 0),
+                cost: 8
+            }]
+        );
+        // a room with a stranger in it cannot be entered, and nobody stops
+        // outside a room
+        let b = read_input("#############\n#.A.........#\n###.#B#C#D###\n  #B#A#C#D#\n  #########")
+            .unwrap();
+        let moves = b.moves();
+        assert!(moves.iter().all(|m| room_of(m.from).is_some()));
+        assert!(moves.iter().all(|m| !DOORS.contains(&m.to)));
+        assert_eq!(moves.len(), 10);
+    }
 
-        //assert_eq!(result, 900099);
+    #[test]
+    fn deep_rooms() {
+        let input = "\
+#############
+#...........#
+###B#C#B#D###
+  #D#C#B#A#
+  #D#B#A#C#
+  #A#D#C#A#
+  #########";
+        let b = read_input(input).unwrap();
+        assert_eq!(b.depth, 4);
+        assert_eq!(b, read_input(SAMPLE).unwrap().unfold().unwrap());
+        let organized = Burrow::organized(4);
+        assert!(organized.unfold().is_ok());
+        let e = organized.unfold().unwrap().unfold().unwrap_err();
+        assert_eq!(e.to_string(), "8:1 expected rooms at most 5 deep");
+    }
+
+    #[test]
+    fn diagram_round_trip() {
+        let b = read_input(SAMPLE).unwrap();
+        assert_eq!(b.to_string(), format!("{}\n", SAMPLE));
+        let unfolded = b.unfold().unwrap();
+        assert_eq!(read_input(&unfolded.to_string()).unwrap(), unfolded);
+    }
+
+    #[test]
+    fn replay_sample() {
+        let s = replay(SAMPLE, false).unwrap();
+        assert!(s.starts_with(SAMPLE));
+        assert!(s.ends_with(&format!("in all:\n{}", Burrow::organized(2))));
+        let last = s.lines().rfind(|l| l.contains("in all")).unwrap();
+        assert!(last.ends_with(", 12521 in all:"), "{}", last);
+    }
+
+    #[test]
+    fn deadlock() {
+        // D and A wait in the hallway, each in the other's way
+        let input = "#############\n#...D.A.....#\n###.#B#C#.###\n  #A#B#C#D#\n  #########";
+        let e = solve_burrow(read_input(input).unwrap()).unwrap_err();
+        assert_eq!(e.to_string(), "the amphipods cannot organize this burrow");
+        assert!(matches!(replay(input, false), Err(SolveError::NoAnswer(_))));
+    }
+
+    #[test]
+    fn bad_diagram() {
+        let e = read_input("#############\n#...........#\n###B#C#B#D###\n  #A#D#C#E#\n  #########")
+            .unwrap_err();
+        assert_eq!(e.to_string(), "4:10 expected '.' or an amphipod 'A' to 'D'");
+        let e = read_input("#############\n#...........#\n###B#C#B#D###\n  #A#D#C#A#\n  ###### ##")
+            .unwrap_err();
+        assert_eq!(e.to_string(), "5:9 expected '#'");
+        let e = read_input("#############\n#...........#\n###B#C#B#D###\n  #A#D#C#C#\n  #########")
+            .unwrap_err();
+        assert_eq!(e.to_string(), "1:1 expected 2 amphipods of each type");
     }
 }
@@ -0,0 +1,50 @@
+use crate::aoc::day23a::{read_input, solve_burrow};
+use crate::aoc::parse::ParseError;
+use crate::aoc::solution::{self, Answer, Parsed, Solution, SolveError};
+
+pub(crate) struct Day23b;
+
+impl Solution for Day23b {
+    fn day(&self) -> u32 {
+        23
+    }
+
+    fn part(&self) -> char {
+        'b'
+    }
+
+    fn title(&self) -> &'static str {
+        "Amphipod"
+    }
+
+    /// The burrow of part one, with the two lines folded away in the
+    /// diagram put back in.
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        Ok(Box::new(read_input(input)?.unfold()?))
+    }
+
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError> {
+        Ok(solve_burrow(solution::unbox(parsed))?.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::aoc::answers;
+    use crate::aoc::day23a::SAMPLE;
+
+    fn solve_input(input: &str) -> Result<u32, SolveError> {
+        solve_burrow(read_input(input)?.unfold()?)
+    }
+
+    #[test]
+    fn result() {
+        answers::check(&Day23b);
+    }
+
+    #[test]
+    fn result_sample() {
+        assert_eq!(solve_input(SAMPLE).unwrap(), 44169);
+    }
+}
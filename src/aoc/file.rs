@@ -1,25 +1,146 @@
-use std::fs::File;
-use std::io;
-use std::io::BufRead;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Cursor, Read};
 use std::path::{Path, PathBuf};
 
-pub fn read_lines(filename: &Path) -> io::Result<io::Lines<io::BufReader<File>>> {
-    let file = File::open(filename)?;
-    let lines = io::BufReader::new(file).lines();
-    Ok(lines)
+use crate::aoc::answers::ANSWERS_FILE;
+
+/// Environment variable naming a directory to take the puzzle inputs from
+/// instead of resources/input.
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+/// Where the input of a puzzle comes from.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InputSource {
+    /// A directory holding the input files by their usual names.
+    Dir(PathBuf),
+    /// One file, whatever puzzle is solved.
+    File(PathBuf),
+    Stdin,
+    /// Input already in memory, called `name` in error messages.
+    Text {
+        name: String,
+        content: String,
+    },
 }
 
+impl InputSource {
+    /// The directory in $AOC_INPUT_DIR, or resources/input if it is not set.
+    pub fn default_dir() -> InputSource {
+        match env::var_os(INPUT_DIR_VAR) {
+            Some(dir) => InputSource::Dir(PathBuf::from(dir)),
+            None => InputSource::Dir(input_dir()),
+        }
+    }
+
+    /// Reads the input stored under `filename` (like "input15.txt") for a
+    /// directory; the other sources ignore the name.
+    pub fn read(&self, filename: &str) -> io::Result<String> {
+        match self {
+            InputSource::Dir(dir) => read_to_string(&dir.join(filename)),
+            InputSource::File(path) => read_to_string(path),
+            InputSource::Stdin => {
+                let mut s = String::new();
+                io::stdin().read_to_string(&mut s)?;
+                Ok(s)
+            }
+            InputSource::Text { content, .. } => Ok(content.clone()),
+        }
+    }
+
+    /// Reads the input like `read`, as bytes that need not be text.
+    pub fn read_bytes(&self, filename: &str) -> io::Result<Vec<u8>> {
+        match self {
+            InputSource::Dir(dir) => fs::read(dir.join(filename)),
+            InputSource::File(path) => fs::read(path),
+            InputSource::Stdin => {
+                let mut bytes = Vec::new();
+                io::stdin().read_to_end(&mut bytes)?;
+                Ok(bytes)
+            }
+            InputSource::Text { content, .. } => Ok(content.clone().into_bytes()),
+        }
+    }
+
+    /// Whether a directory holds an input under `filename`. The other
+    /// sources always have one; whether it can be read shows when reading.
+    pub fn has(&self, filename: &str) -> bool {
+        match self {
+            InputSource::Dir(dir) => dir.join(filename).is_file(),
+            _ => true,
+        }
+    }
+
+    /// Opens the input like `read`, for inputs that are not text or that
+    /// are better read piece by piece.
+    pub fn open(&self, filename: &str) -> io::Result<Box<dyn Read>> {
+        match self {
+            InputSource::Dir(dir) => Ok(Box::new(BufReader::new(File::open(dir.join(filename))?))),
+            InputSource::File(path) => Ok(Box::new(BufReader::new(File::open(path)?))),
+            InputSource::Stdin => Ok(Box::new(io::stdin())),
+            InputSource::Text { content, .. } => {
+                Ok(Box::new(Cursor::new(content.clone().into_bytes())))
+            }
+        }
+    }
+
+    /// Stdin can only be read once; this reads it into a `Text` so that
+    /// several puzzles can be solved on the same input. Errors still name
+    /// it `<stdin>`.
+    pub fn buffered(self) -> io::Result<InputSource> {
+        match self {
+            InputSource::Stdin => Ok(InputSource::Text {
+                name: self.name(""),
+                content: self.read("")?,
+            }),
+            _ => Ok(self),
+        }
+    }
+
+    /// The expected answers kept next to the inputs; only a directory has
+    /// them.
+    pub fn answers_file(&self) -> Option<PathBuf> {
+        match self {
+            InputSource::Dir(dir) => Some(dir.join(ANSWERS_FILE)),
+            _ => None,
+        }
+    }
+
+    /// How the input is called in error messages.
+    pub fn name(&self, filename: &str) -> String {
+        match self {
+            InputSource::Dir(_) => String::from(filename),
+            InputSource::File(path) => path.display().to_string(),
+            InputSource::Stdin => String::from("<stdin>"),
+            InputSource::Text { name, .. } => name.clone(),
+        }
+    }
+}
+
+pub fn read_to_string(filename: &Path) -> io::Result<String> {
+    fs::read_to_string(filename)
+}
+
+/// Reads `filename` from resources/input. The tests check the answers to
+/// these inputs, so they ignore $AOC_INPUT_DIR.
+#[cfg(test)]
+pub fn read_input(filename: &str) -> io::Result<String> {
+    read_to_string(&input(filename))
+}
+
+#[cfg(test)]
 pub fn input(filename: &str) -> PathBuf {
+    input_dir().join(filename)
+}
+
+fn input_dir() -> PathBuf {
     let mut d = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     d.push("resources/input");
-    d.push(filename);
     d
 }
 
 #[cfg(test)]
 mod tests {
-    use std::fs::File;
-    use std::io::{BufReader, Lines};
     use std::path::PathBuf;
 
     use super::*;
@@ -33,23 +154,56 @@ mod tests {
 
     #[test]
     fn read_empty_file() {
-        let bla: Lines<BufReader<File>> = read_lines(&test_file("empty.txt")).unwrap();
+        let bla = read_to_string(&test_file("empty.txt")).unwrap();
         let mut count = 0;
-        for _line in bla {
-            count = count + 1;
+        for _line in bla.lines() {
+            count += 1;
         }
         assert_eq!(count, 0)
     }
 
     #[test]
     fn read_non_empty_file() {
-        let bla: Lines<BufReader<File>> = read_lines(&test_file("twolines.txt")).unwrap();
+        let bla = read_to_string(&test_file("twolines.txt")).unwrap();
         let mut count = 0;
-        for line in bla {
-            count = count + 1;
-            let line = line.unwrap();
+        for line in bla.lines() {
+            count += 1;
             assert_eq!(line, String::from("line") + &count.to_string())
         }
         assert_eq!(count, 2)
     }
+
+    #[test]
+    fn read_from_dir() {
+        let source = InputSource::Dir(test_file(""));
+        let input = source.read("twolines.txt").unwrap();
+        assert_eq!(input.lines().count(), 2);
+        assert_eq!(source.name("twolines.txt"), "twolines.txt");
+    }
+
+    #[test]
+    fn read_from_file_ignores_name() {
+        let source = InputSource::File(test_file("twolines.txt"));
+        let input = source.read("input01.txt").unwrap();
+        assert_eq!(input.lines().next(), Some("line1"));
+    }
+
+    #[test]
+    fn read_from_text() {
+        let source = InputSource::Text {
+            name: String::from("<text>"),
+            content: String::from("1\n2\n"),
+        };
+        assert_eq!(source.read("input01.txt").unwrap(), "1\n2\n");
+        assert_eq!(source.name("input01.txt"), "<text>");
+        assert_eq!(source.clone().buffered().unwrap(), source);
+    }
+
+    #[test]
+    fn missing_file() {
+        let source = InputSource::Dir(test_file(""));
+        assert!(!source.has("nothing.txt"));
+        assert!(source.has("twolines.txt"));
+        assert!(source.read("nothing.txt").is_err());
+    }
 }
@@ -0,0 +1,291 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::aoc::parse::{self, ParseError};
+
+/// A rectangular field of cells, stored row by row. Positions are (x, y)
+/// with (0, 0) in the top left corner.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+const NEIGHBOURS4: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+const NEIGHBOURS8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, value: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    /// Panics if the rows are not all of the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Grid<T> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |r| r.len());
+        let mut cells = Vec::with_capacity(width * height);
+        for row in rows {
+            assert_eq!(row.len(), width, "rows of a grid must have the same length");
+            cells.extend(row);
+        }
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Reads one row per line; `cell` converts a character at a (1-based)
+    /// column. All lines must be as long as the first.
+    pub fn parse<F>(input: &str, cell: F) -> Result<Grid<T>, ParseError>
+    where
+        F: Fn(char, usize) -> Result<T, ParseError>,
+    {
+        Grid::from_lines(parse::lines(input), cell)
+    }
+
+    /// Like `parse`, for lines that are already numbered, e.g. the rest of an
+    /// input after a header.
+    pub fn from_lines<'a, I, F>(lines: I, cell: F) -> Result<Grid<T>, ParseError>
+    where
+        I: Iterator<Item = (usize, &'a str)>,
+        F: Fn(char, usize) -> Result<T, ParseError>,
+    {
+        let mut rows: Vec<Vec<T>> = Vec::new();
+        for (no, line) in lines {
+            let row = line
+                .chars()
+                .enumerate()
+                .map(|(i, c)| cell(c, i + 1))
+                .collect::<Result<Vec<T>, ParseError>>()
+                .map_err(|e| e.on_line(no))?;
+            if !rows.is_empty() && row.len() != rows[0].len() {
+                return Err(ParseError::new(
+                    no,
+                    1,
+                    &format!("{} columns", rows[0].len()),
+                ));
+            }
+            rows.push(row);
+        }
+        Ok(Grid::from_rows(rows))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if x < self.width && y < self.height {
+            Some(&self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if x < self.width && y < self.height {
+            Some(&mut self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// All positions, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    /// A grid of the same size with `f` applied to every cell.
+    pub fn map<U, F: Fn(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks() refuses a size of 0, an empty grid has no rows anyway
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// The positions above, left, right and below of (x, y) that lie in the grid.
+    pub fn neighbours4(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        neighbours4(self.width, self.height, x, y)
+    }
+
+    /// The up to eight positions around (x, y), diagonals included.
+    pub fn neighbours8(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        offsets(self.width, self.height, x, y, &NEIGHBOURS8)
+    }
+}
+
+/// `Grid::neighbours4` for anything else that is `width` by `height`.
+pub fn neighbours4(
+    width: usize,
+    height: usize,
+    x: usize,
+    y: usize,
+) -> impl Iterator<Item = (usize, usize)> {
+    offsets(width, height, x, y, &NEIGHBOURS4)
+}
+
+fn offsets(
+    width: usize,
+    height: usize,
+    x: usize,
+    y: usize,
+    offsets: &'static [(isize, isize)],
+) -> impl Iterator<Item = (usize, usize)> {
+    offsets.iter().filter_map(move |&(dx, dy)| {
+        let nx = x.checked_add_signed(dx).filter(|&nx| nx < width)?;
+        let ny = y.checked_add_signed(dy).filter(|&ny| ny < height)?;
+        Some((nx, ny))
+    })
+}
+
+impl Grid<i8> {
+    /// A grid of single digits, like the height maps of days 9 and 15.
+    pub fn parse_digits(input: &str) -> Result<Grid<i8>, ParseError> {
+        Grid::parse(input, |c, column| {
+            parse::digit(c, 10, column).map(|d| d as i8)
+        })
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y).unwrap_or_else(|| {
+            panic!(
+                "({}, {}) outside of {}x{} grid",
+                x, y, self.width, self.height
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(x, y)
+            .unwrap_or_else(|| panic!("({}, {}) outside of {}x{} grid", x, y, width, height))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for c in row {
+                write!(f, "{}", c)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sorted(mut v: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+        v.sort();
+        v
+    }
+
+    #[test]
+    fn map() {
+        let g = Grid::parse_digits("123\n456").unwrap().map(|&d| d % 2 == 0);
+        assert_eq!(g.width(), 3);
+        assert_eq!(g.iter().filter(|&&even| even).count(), 3);
+        assert!(g[(0, 1)]);
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let g = Grid::parse_digits("123\n456").unwrap();
+        assert_eq!(g.width(), 3);
+        assert_eq!(g.height(), 2);
+        assert_eq!(g[(2, 1)], 6);
+        assert_eq!(g.to_string(), "123\n456");
+    }
+
+    #[test]
+    fn ragged_lines() {
+        let e = Grid::parse_digits("123\n45").unwrap_err();
+        assert_eq!(e.to_string(), "2:1 expected 3 columns");
+    }
+
+    #[test]
+    fn bad_cell() {
+        let e = Grid::parse_digits("123\n4x6").unwrap_err();
+        assert_eq!(e.to_string(), "2:2 expected a digit, found 'x'");
+    }
+
+    #[test]
+    fn get_outside() {
+        let g = Grid::new(2, 2, '.');
+        assert_eq!(g.get(1, 1), Some(&'.'));
+        assert_eq!(g.get(2, 0), None);
+        assert_eq!(g.get(0, 2), None);
+    }
+
+    #[test]
+    fn neighbours_in_corner() {
+        let g = Grid::new(3, 3, 0);
+        let n4: Vec<_> = g.neighbours4(0, 0).collect();
+        assert_eq!(sorted(n4), vec![(0, 1), (1, 0)]);
+        let n8: Vec<_> = g.neighbours8(2, 2).collect();
+        assert_eq!(sorted(n8), vec![(1, 1), (1, 2), (2, 1)]);
+    }
+
+    #[test]
+    fn neighbours_inside() {
+        let g = Grid::new(3, 3, 0);
+        assert_eq!(g.neighbours4(1, 1).count(), 4);
+        assert_eq!(g.neighbours8(1, 1).count(), 8);
+    }
+
+    #[test]
+    fn positions_row_by_row() {
+        let g = Grid::from_rows(vec![vec!['a', 'b'], vec!['c', 'd']]);
+        let cells: String = g.positions().map(|p| g[p]).collect();
+        assert_eq!(cells, "abcd");
+    }
+}
@@ -0,0 +1,126 @@
+use std::fmt;
+
+use crate::aoc::grid::Grid;
+
+/// An image of day 20 that goes on forever. Beyond the pixels kept, all of
+/// it has the background colour.
+pub struct Image {
+    algorithm: [bool; 512],
+    pixels: Grid<bool>,
+    /// Whether the pixels beyond are lit.
+    background: bool,
+}
+
+impl Image {
+    /// `pixels` on a dark background, to be enhanced by `algorithm`.
+    pub fn new(algorithm: [bool; 512], pixels: Grid<bool>) -> Image {
+        Image {
+            algorithm,
+            pixels,
+            background: false,
+        }
+    }
+
+    /// Applies the algorithm `n` times. Each time the pixels kept grow by
+    /// one on every side, as far as the old ones can reach; the background
+    /// turns into what the algorithm makes of all dark or all lit pixels,
+    /// and may well flicker.
+    pub fn enhance(&mut self, n: usize) {
+        for _ in 0..n {
+            self.enhance_once();
+        }
+    }
+
+    /// The pixels kept, `true` for lit ones.
+    pub fn pixels(&self) -> &Grid<bool> {
+        &self.pixels
+    }
+
+    /// The number of lit pixels, unless the background is lit and there are
+    /// infinitely many.
+    pub fn lit(&self) -> Option<usize> {
+        if self.background {
+            return None;
+        }
+        Some(self.pixels.iter().filter(|&&p| p).count())
+    }
+
+    fn enhance_once(&mut self) {
+        let (width, height) = (self.pixels.width() + 2, self.pixels.height() + 2);
+        let mut pixels = Grid::new(width, height, false);
+        for (x, y) in pixels.positions().collect::<Vec<_>>() {
+            // (x, y) is at (x - 1, y - 1) in the old pixels
+            let mut index = 0;
+            for ny in y..y + 3 {
+                for nx in x..x + 3 {
+                    index = index << 1 | self.get(nx as isize - 2, ny as isize - 2) as usize;
+                }
+            }
+            pixels[(x, y)] = self.algorithm[index];
+        }
+        self.pixels = pixels;
+        self.background = self.algorithm[if self.background { 511 } else { 0 }];
+    }
+
+    /// The pixel at (x, y), which may be outside of those kept.
+    fn get(&self, x: isize, y: isize) -> bool {
+        if x < 0 || y < 0 {
+            return self.background;
+        }
+        let pixel = self.pixels.get(x as usize, y as usize);
+        *pixel.unwrap_or(&self.background)
+    }
+}
+
+/// The pixels kept, '#' for lit ones and '.' for dark ones.
+impl fmt::Display for Image {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (y, row) in self.pixels.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for &p in row {
+                write!(f, "{}", if p { '#' } else { '.' })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn image(algorithm: [bool; 512], rows: &str) -> Image {
+        Image::new(algorithm, Grid::parse(rows, |c, _| Ok(c == '#')).unwrap())
+    }
+
+    #[test]
+    fn flickering_background() {
+        // dark pixels around nothing but dark light up, and the other way
+        // round
+        let mut algorithm = [false; 512];
+        algorithm[0] = true;
+        let mut im = image(algorithm, "#");
+        im.enhance(1);
+        assert_eq!(im.lit(), None);
+        assert_eq!(im.to_string(), "...\n...\n...");
+        // only the middle one is surrounded by nothing but dark pixels
+        im.enhance(1);
+        assert_eq!(im.lit(), Some(1));
+        assert_eq!(im.to_string(), ".....\n.....\n..#..\n.....\n.....");
+    }
+
+    #[test]
+    fn identity() {
+        // each pixel keeps its colour, the middle bit of its index
+        let mut algorithm = [false; 512];
+        for (i, a) in algorithm.iter_mut().enumerate() {
+            *a = i & 0b10000 != 0;
+        }
+        let mut im = image(algorithm, "#.\n.#");
+        im.enhance(3);
+        assert_eq!(im.lit(), Some(2));
+        assert_eq!(im.to_string().lines().nth(3).unwrap(), "...#....");
+    }
+}
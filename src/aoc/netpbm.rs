@@ -0,0 +1,195 @@
+use std::path::{Path, PathBuf};
+
+use crate::aoc::grid::Grid;
+
+/// Netpbm images come in a plain form, numbers as text, and a binary one.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Encoding {
+    Plain,
+    Binary,
+}
+
+/// Plain lines should not be longer than this.
+const LINE: usize = 70;
+
+/// A black and white PBM image, `true` for black.
+pub fn pbm(grid: &Grid<bool>, encoding: Encoding) -> Vec<u8> {
+    let (width, height) = (grid.width(), grid.height());
+    match encoding {
+        Encoding::Plain => {
+            let mut image = format!("P1\n{} {}\n", width, height).into_bytes();
+            for row in grid.rows() {
+                // no blanks needed between bits
+                for line in row.chunks(LINE) {
+                    image.extend(line.iter().map(|&p| if p { b'1' } else { b'0' }));
+                    image.push(b'\n');
+                }
+            }
+            image
+        }
+        Encoding::Binary => {
+            let mut image = format!("P4\n{} {}\n", width, height).into_bytes();
+            for row in grid.rows() {
+                // every row starts with a byte of its own
+                for byte in row.chunks(8) {
+                    image.push(
+                        byte.iter()
+                            .enumerate()
+                            .fold(0, |b, (i, &p)| b | (p as u8) << (7 - i)),
+                    );
+                }
+            }
+            image
+        }
+    }
+}
+
+/// A PGM image in shades of grey from 0 for black to `max` for white. Values
+/// above `max` are cut off.
+pub fn pgm(grid: &Grid<u16>, max: u16, encoding: Encoding) -> Vec<u8> {
+    let header = format!(
+        "{}\n{} {}\n{}\n",
+        magic(2, encoding),
+        grid.width(),
+        grid.height(),
+        max
+    );
+    let samples = grid.iter().map(|&v| v.min(max));
+    samples_after(header, samples, max, encoding)
+}
+
+/// A PPM image of red, green and blue values from 0 to `max`. Values above
+/// `max` are cut off.
+pub fn ppm(grid: &Grid<[u16; 3]>, max: u16, encoding: Encoding) -> Vec<u8> {
+    let header = format!(
+        "{}\n{} {}\n{}\n",
+        magic(3, encoding),
+        grid.width(),
+        grid.height(),
+        max
+    );
+    let samples = grid.iter().flat_map(|rgb| rgb.map(|v| v.min(max)));
+    samples_after(header, samples, max, encoding)
+}
+
+/// "P2" and so on; the binary forms are three further.
+fn magic(plain: u8, encoding: Encoding) -> String {
+    match encoding {
+        Encoding::Plain => format!("P{}", plain),
+        Encoding::Binary => format!("P{}", plain + 3),
+    }
+}
+
+/// `header` and then `samples`: in binary a byte each, or two, high byte
+/// first, if `max` needs them.
+fn samples_after<I>(header: String, samples: I, max: u16, encoding: Encoding) -> Vec<u8>
+where
+    I: Iterator<Item = u16>,
+{
+    let mut image = header.into_bytes();
+    match encoding {
+        Encoding::Plain => {
+            let mut line = String::new();
+            for v in samples {
+                let v = v.to_string();
+                if !line.is_empty() && line.len() + 1 + v.len() > LINE {
+                    image.extend(line.as_bytes());
+                    image.push(b'\n');
+                    line.clear();
+                }
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line += &v;
+            }
+            if !line.is_empty() {
+                image.extend(line.as_bytes());
+                image.push(b'\n');
+            }
+        }
+        Encoding::Binary if max < 256 => image.extend(samples.map(|v| v as u8)),
+        Encoding::Binary => image.extend(samples.flat_map(u16::to_be_bytes)),
+    }
+    image
+}
+
+/// Where to write frame `n` of a series named like `path`: "fold.pbm"
+/// becomes "fold-003.pbm".
+pub fn frame_path(path: &Path, n: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(ext) => format!("{}-{:03}.{}", stem, n, ext.to_string_lossy()),
+        None => format!("{}-{:03}", stem, n),
+    };
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bits(s: &str) -> Grid<bool> {
+        Grid::parse(s, |c, _| Ok(c == '#')).unwrap()
+    }
+
+    #[test]
+    fn pbm_plain_and_binary() {
+        let g = bits("#.#.#.#.#\n.........");
+        assert_eq!(pbm(&g, Encoding::Plain), b"P1\n9 2\n101010101\n000000000\n");
+        assert_eq!(
+            pbm(&g, Encoding::Binary),
+            b"P4\n9 2\n\xAA\x80\x00\x00".to_vec()
+        );
+    }
+
+    #[test]
+    fn pgm_plain_and_binary() {
+        let g = Grid::from_rows(vec![vec![0, 5, 9], vec![12, 1, 0]]);
+        assert_eq!(pgm(&g, 9, Encoding::Plain), b"P2\n3 2\n9\n0 5 9 9 1 0\n");
+        assert_eq!(
+            pgm(&g, 9, Encoding::Binary),
+            b"P5\n3 2\n9\n\x00\x05\x09\x09\x01\x00".to_vec()
+        );
+        let wide = Grid::from_rows(vec![vec![258]]);
+        assert_eq!(
+            pgm(&wide, 1000, Encoding::Binary),
+            b"P5\n1 1\n1000\n\x01\x02".to_vec()
+        );
+    }
+
+    #[test]
+    fn ppm_binary() {
+        let g = Grid::from_rows(vec![vec![[255, 0, 0], [0, 128, 255]]]);
+        assert_eq!(
+            ppm(&g, 255, Encoding::Binary),
+            b"P6\n2 1\n255\n\xFF\x00\x00\x00\x80\xFF".to_vec()
+        );
+    }
+
+    #[test]
+    fn plain_lines_stay_short() {
+        let g = Grid::new(100, 1, 255);
+        let image = pgm(&g, 255, Encoding::Plain);
+        let text = String::from_utf8(image).unwrap();
+        assert!(text.lines().all(|l| l.len() <= LINE));
+        assert_eq!(text.split_whitespace().count(), 4 + 100);
+        let wide = Grid::new(100, 1, true);
+        let text = String::from_utf8(pbm(&wide, Encoding::Plain)).unwrap();
+        assert_eq!(
+            text.lines().map(str::len).collect::<Vec<_>>(),
+            [2, 5, 70, 30]
+        );
+    }
+
+    #[test]
+    fn frame_paths() {
+        assert_eq!(
+            frame_path(Path::new("out/fold.pbm"), 3),
+            PathBuf::from("out/fold-003.pbm")
+        );
+        assert_eq!(
+            frame_path(Path::new("steps"), 12),
+            PathBuf::from("steps-012")
+        );
+    }
+}
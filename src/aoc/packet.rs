@@ -0,0 +1,470 @@
+use std::fmt;
+
+use crate::aoc::bits::BitReader;
+#[cfg(test)]
+use crate::aoc::bits::BitWriter;
+use crate::aoc::parse::ParseError;
+
+/// A packet of the day 16 transmissions: a literal value, or an operator on
+/// the packets it contains.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Packet {
+    pub version: u32,
+    pub type_id: u32,
+    pub content: PacketContent,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum PacketContent {
+    LiteralContent(Literal),
+    OperatorContent(Vec<Packet>),
+}
+
+/// The value of a literal packet. A literal can have any number of groups;
+/// those too wide for a u64 are kept as a `BigUint`.
+#[derive(Debug, Eq, PartialEq)]
+pub enum Literal {
+    Small(u64),
+    Big(BigUint),
+}
+
+/// An unsigned integer of any size, as its hex digits, most significant
+/// first and without leading zeros.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BigUint {
+    digits: Vec<u8>,
+}
+
+impl Packet {
+    pub fn parse(r: &mut BitReader) -> Result<Self, ParseError> {
+        let ver = r.read_bits(3)? as u32;
+        let ptype = r.read_bits(3)? as u32;
+        let content = if ptype == 4 {
+            Packet::parse_literal(r)?
+        } else {
+            Packet::parse_operator(r)?
+        };
+        Ok(Packet {
+            version: ver,
+            type_id: ptype,
+            content,
+        })
+    }
+
+    pub fn parse_string(hex: &str) -> Result<Packet, ParseError> {
+        let mut r = BitReader::from_hex(hex);
+        Packet::parse(&mut r)
+    }
+
+    fn parse_literal(b: &mut BitReader) -> Result<PacketContent, ParseError> {
+        let mut val = Literal::Small(0);
+        loop {
+            b.require(5, "the rest of the literal")?;
+            let word = b.read_bits(5)?;
+            val = val.push_digit((word & 0xf) as u8);
+            if (word & 0x10) == 0 {
+                break;
+            }
+        }
+        Ok(PacketContent::LiteralContent(val))
+    }
+
+    fn parse_operator(b: &mut BitReader) -> Result<PacketContent, ParseError> {
+        let length_type_id = b.read_bit()?;
+        let packets: Vec<Packet> = if length_type_id == 0 {
+            let bit_len = b.read_bits(15)? as usize;
+            b.require(bit_len, &format!("sub-packets of {} bits", bit_len))?;
+            Packet::read_packets_by_len(b, bit_len)?
+        } else {
+            let packet_no = b.read_bits(11)? as usize;
+            // the shortest packet is a literal of one group, 11 bits
+            b.require(11 * packet_no, &format!("{} sub-packets", packet_no))?;
+            Packet::read_packets_by_number(b, packet_no)?
+        };
+        Ok(PacketContent::OperatorContent(packets))
+    }
+
+    fn read_packets_by_len(b: &mut BitReader, bit_len: usize) -> Result<Vec<Packet>, ParseError> {
+        let mut packets = Vec::new();
+        let start = b.bits_read();
+        while b.bits_read() - start < bit_len {
+            let packet = Packet::parse(b)?;
+            packets.push(packet);
+        }
+        if b.bits_read() - start > bit_len {
+            let expected = format!("sub-packets ending after {} bits", bit_len);
+            return Err(ParseError::at(b.column(), &expected));
+        }
+        Ok(packets)
+    }
+
+    fn read_packets_by_number(
+        b: &mut BitReader,
+        packet_no: usize,
+    ) -> Result<Vec<Packet>, ParseError> {
+        let mut packets = Vec::new();
+        for _ in 0..packet_no {
+            let packet = Packet::parse(b)?;
+            packets.push(packet);
+        }
+        Ok(packets)
+    }
+}
+
+/// Building and encoding packets, for the tests to check the parser against.
+#[cfg(test)]
+impl Packet {
+    pub fn literal(version: u32, val: u64) -> Packet {
+        Packet {
+            version,
+            type_id: 4,
+            content: PacketContent::LiteralContent(Literal::Small(val)),
+        }
+    }
+
+    pub fn operator(type_id: u32, packets: Vec<Packet>) -> Packet {
+        Packet {
+            version: 0,
+            type_id,
+            content: PacketContent::OperatorContent(packets),
+        }
+    }
+
+    /// The packet as a hex transmission, padded with zeros to whole bytes
+    /// like the puzzle's. Operators give the length of their sub-packets in
+    /// bits when that fits in 15 bits, otherwise their number.
+    pub fn encode(&self) -> String {
+        let mut w = BitWriter::new();
+        self.write(&mut w);
+        w.to_hex()
+    }
+
+    fn write(&self, w: &mut BitWriter) {
+        w.write_bits(self.version as u64, 3);
+        w.write_bits(self.type_id as u64, 3);
+        match &self.content {
+            PacketContent::LiteralContent(val) => Packet::write_literal(w, val),
+            PacketContent::OperatorContent(packets) => Packet::write_operator(w, packets),
+        }
+    }
+
+    fn write_literal(w: &mut BitWriter, val: &Literal) {
+        let digits = val.digits();
+        for (i, &d) in digits.iter().enumerate() {
+            let more = if i + 1 < digits.len() { 0x10 } else { 0 };
+            w.write_bits(more | d as u64, 5);
+        }
+    }
+
+    fn write_operator(w: &mut BitWriter, packets: &[Packet]) {
+        let mut sub = BitWriter::new();
+        for p in packets {
+            p.write(&mut sub);
+        }
+        if sub.len() < 1 << 15 {
+            w.write_bits(0, 1);
+            w.write_bits(sub.len() as u64, 15);
+        } else {
+            assert!(packets.len() < 1 << 11, "too many sub-packets");
+            w.write_bits(1, 1);
+            w.write_bits(packets.len() as u64, 11);
+        }
+        w.append(&sub);
+    }
+}
+
+impl Literal {
+    /// The literal with one more group: shifted left by a hex digit.
+    fn push_digit(self, digit: u8) -> Literal {
+        match self {
+            Literal::Small(v) if v >> 60 == 0 => Literal::Small(v << 4 | digit as u64),
+            Literal::Small(v) => Literal::Big(BigUint::from(v).push_digit(digit)),
+            Literal::Big(v) => Literal::Big(v.push_digit(digit)),
+        }
+    }
+
+    /// Hex digits, most significant first; 0 has one.
+    #[cfg(test)]
+    fn digits(&self) -> Vec<u8> {
+        match self {
+            Literal::Small(v) => BigUint::from(*v).digits,
+            Literal::Big(v) => v.digits.clone(),
+        }
+    }
+}
+
+impl BigUint {
+    fn push_digit(mut self, digit: u8) -> BigUint {
+        if self.digits == [0] {
+            self.digits.clear();
+        }
+        self.digits.push(digit);
+        self
+    }
+
+    /// Long division by 10, returning the remainder.
+    fn div_rem_10(&mut self) -> u8 {
+        let mut rem = 0;
+        for d in self.digits.iter_mut() {
+            let cur = rem * 16 + *d;
+            *d = cur / 10;
+            rem = cur % 10;
+        }
+        let zeros = self.digits.iter().take_while(|&&d| d == 0).count();
+        self.digits.drain(..zeros.min(self.digits.len() - 1));
+        rem
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(v: u64) -> Self {
+        let len = (64 - v.leading_zeros() as usize).div_ceil(4).max(1);
+        BigUint {
+            digits: (0..len).rev().map(|i| (v >> (4 * i) & 0xf) as u8).collect(),
+        }
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut v = self.clone();
+        let mut decimal = vec![];
+        loop {
+            decimal.push(char::from(b'0' + v.div_rem_10()));
+            if v.digits == [0] {
+                break;
+            }
+        }
+        write!(f, "{}", decimal.iter().rev().collect::<String>())
+    }
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Literal::Small(v) => write!(f, "{}", v),
+            Literal::Big(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+/// The hex digits of a transmission, the first line of the input.
+pub fn read_hex(input: &str) -> Result<String, ParseError> {
+    match input.lines().next() {
+        Some(line) => Ok(line.to_string()),
+        None => Err(ParseError::at(1, "a hex transmission")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn operator_sample() {
+        let p = Packet::parse_string("38006F45291200").unwrap();
+        assert_eq!(p.version, 1);
+        assert_eq!(p.type_id, 6);
+        match p.content {
+            PacketContent::LiteralContent(_) => {
+                panic!();
+            }
+            PacketContent::OperatorContent(p) => {
+                assert_eq!(p.len(), 2);
+            }
+        };
+    }
+
+    #[test]
+    fn literal_sample() {
+        let p = Packet::parse_string("D2FE28").unwrap();
+        assert_eq!(p.version, 6);
+        assert_eq!(p.type_id, 4);
+        match p.content {
+            PacketContent::LiteralContent(x) => {
+                assert_eq!(x, Literal::Small(2021))
+            }
+            _ => panic!(),
+        };
+    }
+
+    #[test]
+    fn counted_sample() {
+        let p = Packet::parse_string("EE00D40C823060").unwrap();
+        assert_eq!(p.version, 7);
+        assert_eq!(p.type_id, 3);
+        match p.content {
+            PacketContent::LiteralContent(_) => {
+                panic!();
+            }
+            PacketContent::OperatorContent(vec) => {
+                assert_eq!(vec.len(), 3);
+                match vec[0].content {
+                    PacketContent::LiteralContent(ref x) => assert_eq!(&Literal::Small(1), x),
+                    _ => panic!(),
+                };
+                match vec[1].content {
+                    PacketContent::LiteralContent(ref x) => assert_eq!(&Literal::Small(2), x),
+                    _ => panic!(),
+                };
+                match vec[2].content {
+                    PacketContent::LiteralContent(ref x) => assert_eq!(&Literal::Small(3), x),
+                    _ => panic!(),
+                };
+            }
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let e = Packet::parse_string("D2FE").err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "1:5 expected the rest of the literal, found only 0 bits"
+        );
+    }
+
+    #[test]
+    fn bad_hex() {
+        let e = Packet::parse_string("D2XE28").err().unwrap();
+        assert_eq!(e.to_string(), "1:3 expected a hex digit, found 'X'");
+    }
+
+    #[test]
+    fn literal_wider_than_64_bits() {
+        // 6 bits of header, then 1 followed by 20 zero digits: 16^20
+        let mut w = BitWriter::new();
+        w.write_bits(0b100_100, 6);
+        w.write_bits(0x11, 5);
+        for i in 0..20 {
+            w.write_bits(if i < 19 { 0x10 } else { 0 }, 5);
+        }
+        let p = Packet::parse_string(&w.to_hex()).unwrap();
+        match &p.content {
+            PacketContent::LiteralContent(x) => {
+                assert_eq!(x.to_string(), "1208925819614629174706176")
+            }
+            _ => panic!(),
+        };
+        assert_eq!(Packet::parse_string(&p.encode()).unwrap(), p);
+    }
+
+    #[test]
+    fn literal_at_64_bits() {
+        let p = Packet::literal(0, u64::MAX);
+        assert_eq!(Packet::parse_string(&p.encode()).unwrap(), p);
+    }
+
+    #[test]
+    fn sub_packets_longer_than_transmission() {
+        // an operator with 27 bits of sub-packets, cut off after the length
+        let e = Packet::parse_string("38006C").err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "1:7 expected sub-packets of 27 bits, found only 2 bits"
+        );
+    }
+
+    #[test]
+    fn too_many_sub_packets() {
+        let e = Packet::parse_string("EE00D40C82").err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "1:6 expected 3 sub-packets, found only 22 bits"
+        );
+    }
+
+    #[test]
+    fn sub_packets_longer_than_given() {
+        // "38006F45291200" with the length lowered from 27 to 26 bits
+        let e = Packet::parse_string("38006945291200").err().unwrap();
+        assert_eq!(
+            e.to_string(),
+            "1:13 expected sub-packets ending after 26 bits"
+        );
+    }
+
+    #[test]
+    fn no_transmission() {
+        let e = read_hex("").err().unwrap();
+        assert_eq!(e.to_string(), "1:1 expected a hex transmission");
+    }
+
+    /// A small xorshift generator, enough to vary the packets.
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % n
+        }
+    }
+
+    fn random_packet(rng: &mut Rng, depth: usize) -> Packet {
+        let version = rng.below(8) as u32;
+        if depth == 0 || rng.below(3) == 0 {
+            let bits = rng.below(65);
+            let val = if bits == 0 {
+                0
+            } else {
+                rng.below(1 << (bits - 1)) | 1 << (bits - 1)
+            };
+            return Packet::literal(version, val);
+        }
+        let type_id = [0, 1, 2, 3, 5, 6, 7][rng.below(7) as usize];
+        let count = if type_id >= 5 { 2 } else { 1 + rng.below(4) };
+        let packets = (0..count).map(|_| random_packet(rng, depth - 1)).collect();
+        Packet {
+            version,
+            type_id,
+            content: PacketContent::OperatorContent(packets),
+        }
+    }
+
+    #[test]
+    fn encode_samples() {
+        for hex in ["D2FE28", "38006F45291200"] {
+            assert_eq!(Packet::parse_string(hex).unwrap().encode(), hex);
+        }
+        // counts its sub-packets, which the encoder only does for long ones
+        let p = Packet::parse_string("EE00D40C823060").unwrap();
+        assert_eq!(p.encode(), "EC008540C82306");
+        assert_eq!(Packet::parse_string(&p.encode()).unwrap(), p);
+    }
+
+    #[test]
+    fn encode_literals() {
+        for val in [0, 1, 15, 16, 2021, u32::MAX as u64, u64::MAX] {
+            let p = Packet::literal(5, val);
+            assert_eq!(Packet::parse_string(&p.encode()).unwrap(), p);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut rng = Rng(2021);
+        for _ in 0..500 {
+            let p = random_packet(&mut rng, 4);
+            let hex = p.encode();
+            assert_eq!(Packet::parse_string(&hex).unwrap(), p, "{}", hex);
+        }
+    }
+
+    #[test]
+    fn round_trip_by_number() {
+        // too long for a 15 bit length, so the sub-packets are counted
+        let packets = (0..800).map(|i| Packet::literal(i % 8, 1 << 60)).collect();
+        let p = Packet {
+            version: 1,
+            type_id: 0,
+            content: PacketContent::OperatorContent(packets),
+        };
+        let hex = p.encode();
+        let mut r = BitReader::from_hex(&hex);
+        r.read_bits(6).unwrap();
+        assert_eq!(r.read_bit().unwrap(), 1);
+        assert_eq!(Packet::parse_string(&hex).unwrap(), p);
+    }
+}
@@ -0,0 +1,115 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use regex::Captures;
+
+/// A malformed puzzle input. Line and column are 1-based.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    pub file: Option<String>,
+    pub line: usize,
+    pub column: usize,
+    pub expected: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, expected: &str) -> ParseError {
+        ParseError {
+            file: None,
+            line,
+            column,
+            expected: String::from(expected),
+        }
+    }
+
+    /// Error in a single line of input; the reader looping over the lines
+    /// fills in the line number with `on_line`.
+    pub fn at(column: usize, expected: &str) -> ParseError {
+        ParseError::new(1, column, expected)
+    }
+
+    pub fn on_line(mut self, line: usize) -> ParseError {
+        self.line = line;
+        self
+    }
+
+    pub fn in_file(mut self, file: &str) -> ParseError {
+        self.file = Some(String::from(file));
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file)?;
+        }
+        write!(
+            f,
+            "{}:{} expected {}",
+            self.line, self.column, self.expected
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// The lines of the input, numbered from 1.
+pub fn lines(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input.lines().enumerate().map(|(i, l)| (i + 1, l))
+}
+
+pub fn number<T: FromStr>(s: &str, column: usize) -> Result<T, ParseError> {
+    s.trim()
+        .parse()
+        .map_err(|_| ParseError::at(column, &format!("a number, found '{}'", s)))
+}
+
+pub fn digit(c: char, radix: u32, column: usize) -> Result<u32, ParseError> {
+    c.to_digit(radix).ok_or_else(|| {
+        let what = if radix == 16 {
+            "a hex digit"
+        } else {
+            "a digit"
+        };
+        ParseError::at(column, &format!("{}, found '{}'", what, c))
+    })
+}
+
+/// A number captured by group `i` of a regex match, with the group's position
+/// as column on error.
+pub fn capture<T: FromStr>(caps: &Captures, i: usize) -> Result<T, ParseError> {
+    let m = caps.get(i).unwrap();
+    number(m.as_str(), m.start() + 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let e = ParseError::at(7, "','").on_line(12).in_file("input18.txt");
+        assert_eq!(e.to_string(), "input18.txt:12:7 expected ','");
+    }
+
+    #[test]
+    fn display_without_file() {
+        let e = ParseError::new(3, 1, "a number");
+        assert_eq!(e.to_string(), "3:1 expected a number");
+    }
+
+    #[test]
+    fn bad_number() {
+        let e = number::<i32>("12x", 4).unwrap_err();
+        assert_eq!(e.column, 4);
+        assert_eq!(e.expected, "a number, found '12x'");
+    }
+
+    #[test]
+    fn bad_digit() {
+        let e = digit('g', 16, 2).unwrap_err();
+        assert_eq!(e.to_string(), "1:2 expected a hex digit, found 'g'");
+    }
+}
@@ -0,0 +1,167 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::aoc::grid::{self, Grid};
+
+/// A field to find a way through, moving up, down, left or right.
+pub trait WeightedGrid {
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
+
+    /// What it costs to enter (x, y); `None` if it cannot be entered.
+    fn cost(&self, x: usize, y: usize) -> Option<u32>;
+}
+
+/// A cheapest way and its cost. The steps run from start to goal, both
+/// included; the cost of entering the start is not counted.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Route {
+    pub cost: u32,
+    pub steps: Vec<(usize, usize)>,
+}
+
+/// Risk levels and the like: entering a cell costs its value.
+impl WeightedGrid for Grid<i8> {
+    fn width(&self) -> usize {
+        self.width()
+    }
+
+    fn height(&self) -> usize {
+        self.height()
+    }
+
+    fn cost(&self, x: usize, y: usize) -> Option<u32> {
+        self.get(x, y).map(|&c| c as u32)
+    }
+}
+
+pub fn dijkstra<G: WeightedGrid>(
+    grid: &G,
+    start: (usize, usize),
+    goal: (usize, usize),
+) -> Option<Route> {
+    search(grid, start, goal, |_| 0)
+}
+
+/// Like `dijkstra`, but heads for the goal first. The Manhattan distance it
+/// estimates the rest of the way with is only a lower bound if every step
+/// costs at least 1; with cheaper steps the route may not be the cheapest.
+pub fn a_star<G: WeightedGrid>(
+    grid: &G,
+    start: (usize, usize),
+    goal: (usize, usize),
+) -> Option<Route> {
+    search(grid, start, goal, |(x, y)| {
+        (x.abs_diff(goal.0) + y.abs_diff(goal.1)) as u32
+    })
+}
+
+fn search<G, H>(grid: &G, start: (usize, usize), goal: (usize, usize), estimate: H) -> Option<Route>
+where
+    G: WeightedGrid,
+    H: Fn((usize, usize)) -> u32,
+{
+    let (width, height) = (grid.width(), grid.height());
+    let mut cost = Grid::new(width, height, u32::MAX);
+    let mut from: Grid<Option<(usize, usize)>> = Grid::new(width, height, None);
+    let mut queue = BinaryHeap::new();
+    cost[start] = 0;
+    queue.push(Reverse((estimate(start), 0, start)));
+    while let Some(Reverse((_, c, pos))) = queue.pop() {
+        if pos == goal {
+            return Some(Route {
+                cost: c,
+                steps: steps_to(&from, goal),
+            });
+        }
+        if c > cost[pos] {
+            // already reached more cheaply
+            continue;
+        }
+        for (x, y) in grid::neighbours4(width, height, pos.0, pos.1) {
+            if let Some(step) = grid.cost(x, y) {
+                let next = c + step;
+                if next < cost[(x, y)] {
+                    cost[(x, y)] = next;
+                    from[(x, y)] = Some(pos);
+                    queue.push(Reverse((next + estimate((x, y)), next, (x, y))));
+                }
+            }
+        }
+    }
+    None
+}
+
+fn steps_to(from: &Grid<Option<(usize, usize)>>, goal: (usize, usize)) -> Vec<(usize, usize)> {
+    let mut steps = vec![goal];
+    while let Some(p) = from[*steps.last().unwrap()] {
+        steps.push(p);
+    }
+    steps.reverse();
+    steps
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Walls are '#', every other cell costs 1.
+    struct Maze(Grid<char>);
+
+    impl WeightedGrid for Maze {
+        fn width(&self) -> usize {
+            self.0.width()
+        }
+
+        fn height(&self) -> usize {
+            self.0.height()
+        }
+
+        fn cost(&self, x: usize, y: usize) -> Option<u32> {
+            match self.0[(x, y)] {
+                '#' => None,
+                _ => Some(1),
+            }
+        }
+    }
+
+    fn maze(s: &str) -> Maze {
+        Maze(Grid::parse(s, |c, _| Ok(c)).unwrap())
+    }
+
+    #[test]
+    fn around_the_wall() {
+        let m = maze("...\n##.\n...");
+        let route = dijkstra(&m, (0, 0), (0, 2)).unwrap();
+        assert_eq!(route.cost, 6);
+        assert_eq!(
+            route.steps,
+            vec![(0, 0), (1, 0), (2, 0), (2, 1), (2, 2), (1, 2), (0, 2)]
+        );
+    }
+
+    #[test]
+    fn walled_in() {
+        let m = maze("..#\n.##\n#..");
+        assert_eq!(dijkstra(&m, (0, 0), (2, 2)), None);
+        assert_eq!(a_star(&m, (0, 0), (2, 2)), None);
+    }
+
+    #[test]
+    fn start_is_goal() {
+        let g = Grid::parse_digits("19\n11").unwrap();
+        let route = dijkstra(&g, (1, 1), (1, 1)).unwrap();
+        assert_eq!(route.cost, 0);
+        assert_eq!(route.steps, vec![(1, 1)]);
+    }
+
+    #[test]
+    fn a_star_finds_the_cheapest() {
+        let g = Grid::parse_digits("1163\n1381\n2136\n3694").unwrap();
+        let d = dijkstra(&g, (0, 0), (3, 3)).unwrap();
+        let a = a_star(&g, (0, 0), (3, 3)).unwrap();
+        assert_eq!(d.cost, a.cost);
+        let summed: u32 = d.steps[1..].iter().map(|&p| g[p] as u32).sum();
+        assert_eq!(summed, d.cost);
+    }
+}
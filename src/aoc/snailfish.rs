@@ -0,0 +1,408 @@
+use std::fmt;
+use std::iter::{Enumerate, Peekable, Sum};
+use std::ops::Add;
+use std::str::{Chars, FromStr};
+
+use crate::aoc::parse::{self, ParseError};
+
+/// A snailfish number of day 18: a pair of two elements, each a regular
+/// number or another pair.
+///
+/// It is kept as its regular numbers from left to right, each with its
+/// depth, the number of pairs it is inside of. Exploding and splitting
+/// change that list in place, so nothing is left over from the pairs they
+/// take apart.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SnailNumber {
+    leaves: Vec<Leaf>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Leaf {
+    value: u32,
+    depth: usize,
+}
+
+/// One step of reducing a snailfish number.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReductionStep {
+    pub action: Action,
+    /// Where it happened, see `SnailNumber::path`.
+    pub path: String,
+    /// The number after this step.
+    pub number: SnailNumber,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Action {
+    Explode,
+    Split,
+}
+
+/// Pairs nested inside this many pairs explode.
+const MAX_DEPTH: usize = 4;
+
+impl SnailNumber {
+    /// Three times the magnitude of the left element plus twice that of
+    /// the right one; a regular number is its own magnitude.
+    pub fn magnitude(&self) -> u32 {
+        let mut stack: Vec<Leaf> = Vec::new();
+        for &leaf in &self.leaves {
+            stack.push(leaf);
+            while stack.len() >= 2 && stack[stack.len() - 2].depth == stack[stack.len() - 1].depth {
+                let rite = stack.pop().unwrap();
+                let left = stack.pop().unwrap();
+                stack.push(Leaf {
+                    value: 3 * left.value + 2 * rite.value,
+                    depth: left.depth - 1,
+                });
+            }
+        }
+        stack[0].value
+    }
+
+    /// Whether no pair is nested too deep and no regular number is 10 or more,
+    /// so that adding it to another one needs no reducing before.
+    pub fn is_reduced(&self) -> bool {
+        self.next_action().is_none()
+    }
+
+    /// The pair of both numbers as it is before reducing.
+    pub fn pair(&self, o: &SnailNumber) -> SnailNumber {
+        let leaves = self.leaves.iter().chain(&o.leaves);
+        SnailNumber {
+            leaves: leaves
+                .map(|l| Leaf {
+                    value: l.value,
+                    depth: l.depth + 1,
+                })
+                .collect(),
+        }
+    }
+
+    /// Every explode and split it takes to reduce this number, each with the
+    /// number it leaves. There are none if it is reduced already.
+    pub fn reduction(&self) -> Vec<ReductionStep> {
+        let mut n = self.clone();
+        let mut steps = vec![];
+        while let Some((action, i)) = n.next_action() {
+            let mut path = n.path(i);
+            if action == Action::Explode {
+                // the pair, not its left number
+                path.pop();
+            }
+            n.apply(action, i);
+            steps.push(ReductionStep {
+                action,
+                path,
+                number: n.clone(),
+            });
+        }
+        steps
+    }
+
+    fn reduce(&mut self) {
+        while let Some((action, i)) = self.next_action() {
+            self.apply(action, i);
+        }
+    }
+
+    /// What to do next to reduce, and the leaf to do it at: the leftmost
+    /// pair of regular numbers nested too deep explodes, or else the leftmost
+    /// regular number of 10 or more splits.
+    fn next_action(&self) -> Option<(Action, usize)> {
+        let explode = self
+            .leaves
+            .windows(2)
+            .position(|w| w[0].depth > MAX_DEPTH && w[0].depth == w[1].depth);
+        match explode {
+            Some(i) => Some((Action::Explode, i)),
+            None => self
+                .leaves
+                .iter()
+                .position(|l| l.value >= 10)
+                .map(|i| (Action::Split, i)),
+        }
+    }
+
+    fn apply(&mut self, action: Action, i: usize) {
+        match action {
+            Action::Explode => self.explode(i),
+            Action::Split => self.split(i),
+        }
+    }
+
+    /// Explodes the pair whose left number is leaf `i`.
+    fn explode(&mut self, i: usize) {
+        let (left, rite) = (self.leaves[i], self.leaves[i + 1]);
+        if i > 0 {
+            self.leaves[i - 1].value += left.value;
+        }
+        if i + 2 < self.leaves.len() {
+            self.leaves[i + 2].value += rite.value;
+        }
+        self.leaves[i] = Leaf {
+            value: 0,
+            depth: left.depth - 1,
+        };
+        self.leaves.remove(i + 1);
+    }
+
+    /// Splits leaf `i` into a pair.
+    fn split(&mut self, i: usize) {
+        let Leaf { value, depth } = self.leaves[i];
+        self.leaves[i] = Leaf {
+            value: value / 2,
+            depth: depth + 1,
+        };
+        self.leaves.insert(
+            i + 1,
+            Leaf {
+                value: value.div_ceil(2),
+                depth: depth + 1,
+            },
+        );
+    }
+
+    /// The way from the outermost pair down to leaf `i`, 'L' for a left
+    /// element and 'R' for a right one.
+    fn path(&self, i: usize) -> String {
+        let mut path: Vec<char> = vec![];
+        for leaf in &self.leaves[..=i] {
+            // the next leaf starts the right element of the innermost pair
+            // that is still on its left one
+            while path.last() == Some(&'R') {
+                path.pop();
+            }
+            if let Some(last) = path.last_mut() {
+                *last = 'R';
+            }
+            path.resize(leaf.depth, 'L');
+        }
+        path.into_iter().collect()
+    }
+
+    fn parse_iter(
+        &mut self,
+        input: &mut Peekable<Enumerate<Chars>>,
+        depth: usize,
+        end: usize,
+    ) -> Result<(), ParseError> {
+        let &(start, c) = input
+            .peek()
+            .ok_or_else(|| ParseError::at(end, "'[' or a number"))?;
+        // a snailfish number is a pair, only its elements can be plain numbers
+        if c.is_ascii_digit() && depth > 0 {
+            input.next();
+            let value = parse::digit(c, 10, start + 1)?;
+            // larger ones would not be reduced, and could overflow the sums
+            // of exploding and the magnitude
+            if input.peek().is_some_and(|&(_, c)| c.is_ascii_digit()) {
+                return Err(ParseError::at(start + 1, "a regular number below 10"));
+            }
+            self.leaves.push(Leaf { value, depth });
+            Ok(())
+        } else {
+            SnailNumber::expect(input, '[', end)?;
+            self.parse_iter(input, depth + 1, end)?;
+            SnailNumber::expect(input, ',', end)?;
+            self.parse_iter(input, depth + 1, end)?;
+            SnailNumber::expect(input, ']', end)
+        }
+    }
+
+    fn expect(
+        input: &mut Peekable<Enumerate<Chars>>,
+        expected: char,
+        end: usize,
+    ) -> Result<(), ParseError> {
+        match input.next() {
+            Some((_, c)) if c == expected => Ok(()),
+            Some((i, _)) => Err(ParseError::at(i + 1, &format!("'{}'", expected))),
+            None => Err(ParseError::at(end, &format!("'{}'", expected))),
+        }
+    }
+
+    /// Writes the element at depth `depth` that starts with leaf `*i`.
+    fn fmt_element(&self, f: &mut fmt::Formatter, i: &mut usize, depth: usize) -> fmt::Result {
+        let leaf = self.leaves[*i];
+        if leaf.depth == depth {
+            *i += 1;
+            return write!(f, "{}", leaf.value);
+        }
+        write!(f, "[")?;
+        self.fmt_element(f, i, depth + 1)?;
+        write!(f, ",")?;
+        self.fmt_element(f, i, depth + 1)?;
+        write!(f, "]")
+    }
+}
+
+/// Errors are in a single line, see `ParseError::at`.
+impl FromStr for SnailNumber {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<SnailNumber, ParseError> {
+        let mut n = SnailNumber { leaves: vec![] };
+        let end = s.chars().count() + 1;
+        let mut p = s.chars().enumerate().peekable();
+        n.parse_iter(&mut p, 0, end)?;
+        match p.next() {
+            None => Ok(n),
+            Some((i, _)) => Err(ParseError::at(i + 1, "end of line")),
+        }
+    }
+}
+
+/// The pair of both numbers, reduced.
+impl Add for &SnailNumber {
+    type Output = SnailNumber;
+
+    fn add(self, o: &SnailNumber) -> SnailNumber {
+        let mut sum = self.pair(o);
+        sum.reduce();
+        sum
+    }
+}
+
+impl Add for SnailNumber {
+    type Output = SnailNumber;
+
+    fn add(self, o: SnailNumber) -> SnailNumber {
+        &self + &o
+    }
+}
+
+/// Adds up from the left. Panics on an empty list: snailfish numbers have
+/// no zero.
+impl Sum for SnailNumber {
+    fn sum<I: Iterator<Item = SnailNumber>>(iter: I) -> SnailNumber {
+        iter.reduce(|a, b| a + b)
+            .expect("a sum of snailfish numbers needs at least one")
+    }
+}
+
+impl<'a> Sum<&'a SnailNumber> for SnailNumber {
+    fn sum<I: Iterator<Item = &'a SnailNumber>>(iter: I) -> SnailNumber {
+        iter.cloned().sum()
+    }
+}
+
+impl fmt::Display for SnailNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_element(f, &mut 0, 0)
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Action::Explode => write!(f, "explode"),
+            Action::Split => write!(f, "split"),
+        }
+    }
+}
+
+/// "explode at LLLL: [[[[0,9],2],3],4]"
+impl fmt::Display for ReductionStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            "the top"
+        } else {
+            &self.path
+        };
+        write!(f, "{} at {}: {}", self.action, path, self.number)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn snail(s: &str) -> SnailNumber {
+        s.parse().unwrap()
+    }
+
+    fn reduced(s: &str) -> String {
+        let mut n = snail(s);
+        n.reduce();
+        n.to_string()
+    }
+
+    #[test]
+    fn reduction() {
+        assert_eq!(reduced("[[[[[9,8],1],2],3],4]"), "[[[[0,9],2],3],4]");
+        assert_eq!(reduced("[7,[6,[5,[4,[3,2]]]]]"), "[7,[6,[5,[7,0]]]]");
+    }
+
+    #[test]
+    fn reduction_steps() {
+        let n = snail("[[[[4,3],4],4],[7,[[8,4],9]]]").pair(&snail("[1,1]"));
+        assert_eq!(n.to_string(), "[[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]");
+        let steps: Vec<String> = n.reduction().iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            steps,
+            [
+                "explode at LLLL: [[[[0,7],4],[7,[[8,4],9]]],[1,1]]",
+                "explode at LRRL: [[[[0,7],4],[15,[0,13]]],[1,1]]",
+                "split at LRL: [[[[0,7],4],[[7,8],[0,13]]],[1,1]]",
+                "split at LRRR: [[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]",
+                "explode at LRRR: [[[[0,7],4],[[7,8],[6,0]]],[8,1]]",
+            ]
+        );
+        assert!(snail("[[1,2],3]").reduction().is_empty());
+        assert!(snail("[[1,2],3]").is_reduced());
+        assert!(!snail("[[[[[9,8],1],2],3],4]").is_reduced());
+    }
+
+    #[test]
+    fn add() {
+        let sum = snail("[[[[4,3],4],4],[7,[[8,4],9]]]") + snail("[1,1]");
+        assert_eq!(sum.to_string(), "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
+    }
+
+    #[test]
+    fn sum() {
+        let list: Vec<SnailNumber> = ["[1,1]", "[2,2]", "[3,3]", "[4,4]", "[5,5]", "[6,6]"]
+            .iter()
+            .map(|s| snail(s))
+            .collect();
+        let sum: SnailNumber = list.iter().sum();
+        assert_eq!(sum.to_string(), "[[[[5,0],[7,4]],[5,5]],[6,6]]");
+        assert_eq!(sum.magnitude(), 1137);
+    }
+
+    #[test]
+    fn magnitude() {
+        assert_eq!(snail("[[1,2],[[3,4],5]]").magnitude(), 143);
+        assert_eq!(snail("[9,1]").magnitude(), 29);
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let s = "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]";
+        assert_eq!(snail(s).to_string(), s);
+    }
+
+    #[test]
+    fn trailing_input() {
+        let e = "[1,2]]".parse::<SnailNumber>().unwrap_err();
+        assert_eq!(e.to_string(), "1:6 expected end of line");
+    }
+
+    #[test]
+    fn plain_number() {
+        let e = "9".parse::<SnailNumber>().unwrap_err();
+        assert_eq!(e.to_string(), "1:1 expected '['");
+    }
+
+    #[test]
+    fn regular_number_too_large() {
+        let e = "[10,1]".parse::<SnailNumber>().unwrap_err();
+        assert_eq!(e.to_string(), "1:2 expected a regular number below 10");
+        let e = "[[[[[4294967295,1],1],1],1],1]"
+            .parse::<SnailNumber>()
+            .unwrap_err();
+        assert_eq!(e.to_string(), "1:6 expected a regular number below 10");
+    }
+}
@@ -0,0 +1,142 @@
+use std::any::Any;
+use std::error::Error;
+use std::fmt;
+
+use crate::aoc::file::InputSource;
+use crate::aoc::parse::ParseError;
+
+/// The result of a puzzle part. Most puzzles ask for a number, a few
+/// (like the folded paper of day 13) for a picture that has to be read.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Answer {
+    Number(i128),
+    Text(String),
+}
+
+/// Why a puzzle has no answer: its input is malformed, or it is read fine
+/// but leads to no answer, like an expression whose value overflows.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SolveError {
+    Parse(ParseError),
+    NoAnswer(String),
+}
+
+/// The input of a puzzle as read by its `parse`, whatever type that puzzle
+/// works on.
+pub type Parsed = Box<dyn Any>;
+
+/// One part of one day's puzzle.
+pub trait Solution {
+    fn day(&self) -> u32;
+    fn part(&self) -> char;
+    fn title(&self) -> &'static str;
+
+    /// Reads the input for `solve_parsed`; apart so that the benchmark can
+    /// time parsing and solving on the same run.
+    fn parse(&self, input: &str) -> Result<Parsed, ParseError>;
+    fn solve_parsed(&self, parsed: Parsed) -> Result<Answer, SolveError>;
+
+    fn solve(&self, input: &str) -> Result<Answer, SolveError> {
+        self.solve_parsed(self.parse(input)?)
+    }
+
+    /// Short name like "15b", as used on the command line.
+    fn name(&self) -> String {
+        format!("{:02}{}", self.day(), self.part())
+    }
+
+    /// Name of the puzzle input file in the input directory.
+    fn input_file(&self) -> String {
+        format!("input{:02}.txt", self.day())
+    }
+
+    /// Reads the input from `source` and solves it. Errors name the input
+    /// they were found in.
+    fn solve_from(&self, source: &InputSource) -> Result<Answer, Box<dyn Error>> {
+        let name = source.name(&self.input_file());
+        let input = source
+            .read(&self.input_file())
+            .map_err(|e| format!("cannot read {}: {}", name, e))?;
+        Ok(self.solve(&input).map_err(|e| e.in_file(&name))?)
+    }
+}
+
+/// Takes the input out of what `parse` returned. Panics if it is not a `T`,
+/// that is if it was parsed for another puzzle.
+pub fn unbox<T: 'static>(parsed: Parsed) -> T {
+    *parsed
+        .downcast()
+        .unwrap_or_else(|_| panic!("input parsed for another puzzle"))
+}
+
+impl SolveError {
+    /// Names the input the error was found in.
+    pub fn in_file(self, file: &str) -> SolveError {
+        match self {
+            SolveError::Parse(e) => SolveError::Parse(e.in_file(file)),
+            SolveError::NoAnswer(why) => SolveError::NoAnswer(format!("{}: {}", file, why)),
+        }
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveError::Parse(e) => write!(f, "{}", e),
+            SolveError::NoAnswer(why) => write!(f, "{}", why),
+        }
+    }
+}
+
+impl Error for SolveError {}
+
+impl From<ParseError> for SolveError {
+    fn from(e: ParseError) -> Self {
+        SolveError::Parse(e)
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl From<i32> for Answer {
+    fn from(n: i32) -> Self {
+        Answer::Number(n as i128)
+    }
+}
+
+impl From<u32> for Answer {
+    fn from(n: u32) -> Self {
+        Answer::Number(n as i128)
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(n: i64) -> Self {
+        Answer::Number(n as i128)
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(n: u64) -> Self {
+        Answer::Number(n as i128)
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(n: usize) -> Self {
+        Answer::Number(n as i128)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
@@ -0,0 +1,280 @@
+use std::ops::{Add, Mul, Sub};
+
+/// A point or offset in 3D space.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Vec3 {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+/// A 3x3 integer matrix, like the rotations that turn a `Vec3` by quarter
+/// turns. Rows are stored one after the other.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Mat3 {
+    rows: [[i32; 3]; 3],
+}
+
+impl Vec3 {
+    pub const ZERO: Vec3 = Vec3::new(0, 0, 0);
+
+    pub const fn new(x: i32, y: i32, z: i32) -> Vec3 {
+        Vec3 { x, y, z }
+    }
+
+    /// Sum of the absolute coordinates: the Manhattan distance from the
+    /// origin.
+    pub fn manhattan(self) -> i32 {
+        self.x.abs() + self.y.abs() + self.z.abs()
+    }
+
+    const fn to_array(self) -> [i32; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+impl Mat3 {
+    pub const IDENTITY: Mat3 = Mat3::new([[1, 0, 0], [0, 1, 0], [0, 0, 1]]);
+
+    /// A quarter turn around the x axis, taking y to z.
+    pub const ROT_X: Mat3 = Mat3::new([[1, 0, 0], [0, 0, -1], [0, 1, 0]]);
+
+    /// A quarter turn around the y axis, taking z to x.
+    pub const ROT_Y: Mat3 = Mat3::new([[0, 0, 1], [0, 1, 0], [-1, 0, 0]]);
+
+    /// A quarter turn around the z axis, taking x to y.
+    pub const ROT_Z: Mat3 = Mat3::new([[0, -1, 0], [1, 0, 0], [0, 0, 1]]);
+
+    pub const fn new(rows: [[i32; 3]; 3]) -> Mat3 {
+        Mat3 { rows }
+    }
+
+    /// The matrix that applies `other` first and then `self`.
+    pub const fn compose(self, other: Mat3) -> Mat3 {
+        let (a, b) = (self.rows, other.rows);
+        let mut rows = [[0; 3]; 3];
+        let mut i = 0;
+        while i < 3 {
+            let mut j = 0;
+            while j < 3 {
+                rows[i][j] = a[i][0] * b[0][j] + a[i][1] * b[1][j] + a[i][2] * b[2][j];
+                j += 1;
+            }
+            i += 1;
+        }
+        Mat3 { rows }
+    }
+
+    pub const fn transpose(self) -> Mat3 {
+        let r = self.rows;
+        Mat3::new([
+            [r[0][0], r[1][0], r[2][0]],
+            [r[0][1], r[1][1], r[2][1]],
+            [r[0][2], r[1][2], r[2][2]],
+        ])
+    }
+
+    pub const fn determinant(self) -> i32 {
+        let r = self.rows;
+        r[0][0] * (r[1][1] * r[2][2] - r[1][2] * r[2][1])
+            - r[0][1] * (r[1][0] * r[2][2] - r[1][2] * r[2][0])
+            + r[0][2] * (r[1][0] * r[2][1] - r[1][1] * r[2][0])
+    }
+
+    /// The inverse, if it has integer entries, which is the case exactly if
+    /// the determinant is 1 or -1. For a rotation it is the transpose.
+    pub const fn inverse(self) -> Option<Mat3> {
+        let det = self.determinant();
+        if det.abs() != 1 {
+            return None;
+        }
+        let r = self.rows;
+        let mut rows = [[0; 3]; 3];
+        let mut i = 0;
+        while i < 3 {
+            let mut j = 0;
+            while j < 3 {
+                // the adjugate is the transposed cofactor matrix; the
+                // cofactor of (j, i) by the cyclic rule
+                let (j1, j2) = ((j + 1) % 3, (j + 2) % 3);
+                let (i1, i2) = ((i + 1) % 3, (i + 2) % 3);
+                rows[i][j] = (r[j1][i1] * r[j2][i2] - r[j1][i2] * r[j2][i1]) * det;
+                j += 1;
+            }
+            i += 1;
+        }
+        Some(Mat3::new(rows))
+    }
+
+    pub const fn apply(self, v: Vec3) -> Vec3 {
+        let (r, v) = (self.rows, v.to_array());
+        Vec3::new(
+            r[0][0] * v[0] + r[0][1] * v[1] + r[0][2] * v[2],
+            r[1][0] * v[0] + r[1][1] * v[1] + r[1][2] * v[2],
+            r[2][0] * v[0] + r[2][1] * v[1] + r[2][2] * v[2],
+        )
+    }
+
+    const fn same(self, other: Mat3) -> bool {
+        let mut i = 0;
+        while i < 3 {
+            let mut j = 0;
+            while j < 3 {
+                if self.rows[i][j] != other.rows[i][j] {
+                    return false;
+                }
+                j += 1;
+            }
+            i += 1;
+        }
+        true
+    }
+}
+
+/// The 24 ways to turn a cube onto itself, the identity first: everything
+/// that quarter turns around the axes lead to. Checked when compiling to be
+/// proper rotations that undo each other, the inverse of each being its
+/// transpose and among them.
+pub const ROTATIONS: [Mat3; 24] = rotations();
+
+const fn rotations() -> [Mat3; 24] {
+    let turns = [Mat3::ROT_X, Mat3::ROT_Y, Mat3::ROT_Z];
+    let mut all = [Mat3::IDENTITY; 24];
+    let mut found = 1;
+    let mut i = 0;
+    while i < found {
+        let mut t = 0;
+        while t < turns.len() {
+            let next = all[i].compose(turns[t]);
+            let mut known = false;
+            let mut k = 0;
+            while k < found {
+                known = known || all[k].same(next);
+                k += 1;
+            }
+            if !known {
+                all[found] = next;
+                found += 1;
+            }
+            t += 1;
+        }
+        i += 1;
+    }
+    assert!(found == 24, "quarter turns make 24 rotations");
+    let mut k = 0;
+    while k < found {
+        let rot = all[k];
+        assert!(rot.determinant() == 1, "rotations keep handedness");
+        let inverse = match rot.inverse() {
+            Some(inverse) => inverse,
+            None => panic!("rotations can be undone"),
+        };
+        assert!(inverse.same(rot.transpose()), "rotations are orthogonal");
+        let mut known = false;
+        let mut m = 0;
+        while m < found {
+            known = known || all[m].same(inverse);
+            m += 1;
+        }
+        assert!(known, "rotations are undone by one of them");
+        k += 1;
+    }
+    all
+}
+
+impl Add for Vec3 {
+    type Output = Vec3;
+
+    fn add(self, o: Vec3) -> Vec3 {
+        Vec3::new(self.x + o.x, self.y + o.y, self.z + o.z)
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Vec3;
+
+    fn sub(self, o: Vec3) -> Vec3 {
+        Vec3::new(self.x - o.x, self.y - o.y, self.z - o.z)
+    }
+}
+
+impl Mul for Mat3 {
+    type Output = Mat3;
+
+    fn mul(self, o: Mat3) -> Mat3 {
+        self.compose(o)
+    }
+}
+
+impl Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+
+    fn mul(self, v: Vec3) -> Vec3 {
+        self.apply(v)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    const P: Vec3 = Vec3::new(1, 2, 3);
+
+    #[test]
+    fn quarter_turns() {
+        assert_eq!(Mat3::ROT_X * P, Vec3::new(1, -3, 2));
+        assert_eq!(Mat3::ROT_Y * P, Vec3::new(3, 2, -1));
+        assert_eq!(Mat3::ROT_Z * P, Vec3::new(-2, 1, 3));
+        assert_eq!(Mat3::ROT_X * Mat3::ROT_X * P, Vec3::new(1, -2, -3));
+    }
+
+    #[test]
+    fn four_quarter_turns_are_none() {
+        for r in [Mat3::ROT_X, Mat3::ROT_Y, Mat3::ROT_Z] {
+            assert_eq!(r * r * r * r, Mat3::IDENTITY);
+        }
+    }
+
+    #[test]
+    fn rotations_are_distinct() {
+        let distinct: HashSet<Mat3> = ROTATIONS.iter().copied().collect();
+        assert_eq!(distinct.len(), 24);
+        assert_eq!(ROTATIONS[0], Mat3::IDENTITY);
+    }
+
+    #[test]
+    fn rotations_are_closed() {
+        for a in ROTATIONS {
+            for b in ROTATIONS {
+                assert!(ROTATIONS.contains(&(a * b)), "{:?} * {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn rotations_have_inverses() {
+        for r in ROTATIONS {
+            assert_eq!(r.determinant(), 1);
+            let inverse = r.inverse().unwrap();
+            assert_eq!(inverse, r.transpose());
+            assert!(ROTATIONS.contains(&inverse));
+            assert_eq!(r * inverse, Mat3::IDENTITY);
+        }
+    }
+
+    #[test]
+    fn rotations_keep_distances() {
+        let images: HashSet<Vec3> = ROTATIONS.iter().map(|&r| r * P).collect();
+        assert_eq!(images.len(), 24);
+        assert!(images.iter().all(|v| v.manhattan() == P.manhattan()));
+    }
+
+    #[test]
+    fn inverse_of_other_matrices() {
+        let shear = Mat3::new([[1, 2, 0], [0, 1, 0], [0, 0, 1]]);
+        assert_eq!(shear * shear.inverse().unwrap(), Mat3::IDENTITY);
+        assert_eq!(Mat3::new([[2, 0, 0], [0, 1, 0], [0, 0, 1]]).inverse(), None);
+    }
+}
@@ -1,3 +1,489 @@
 mod aoc;
 
-fn main() {}
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::aoc::answers::{Answers, Verdict};
+use crate::aoc::bench::{self, Baseline};
+use crate::aoc::day11a;
+use crate::aoc::day13b;
+use crate::aoc::day15b::{self, Day15b};
+use crate::aoc::day16b::{self, Day16b};
+use crate::aoc::day18a::{self, Day18a};
+use crate::aoc::day20a;
+use crate::aoc::day21a::{self, Day21a, DeterministicDie, Die, ScriptedDie, SeededDie};
+use crate::aoc::day21b::DiracGame;
+use crate::aoc::day23a::{self, Day23a};
+use crate::aoc::file::{self, InputSource};
+use crate::aoc::netpbm::{self, Encoding};
+use crate::aoc::solution::Solution;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    let runs = match take_option(&mut args, "--runs") {
+        Some(runs) => runs.parse().unwrap_or_else(|_| usage()),
+        None => 10,
+    };
+    let save = take_option(&mut args, "--save");
+    let baseline = take_option(&mut args, "--baseline").map(read_baseline);
+    let answers_file = take_option(&mut args, "--answers").map(PathBuf::from);
+    let factor = take_option(&mut args, "--factor").map_or(5, positive);
+    let wrap = take_option(&mut args, "--wrap").map_or(9, positive);
+    let ppm = take_option(&mut args, "--ppm");
+    let binary = take_flag(&mut args, "--binary");
+    let steps = take_flag(&mut args, "--steps");
+    let seed = take_option(&mut args, "--seed");
+    let script = take_option(&mut args, "--script");
+    let unfold = take_flag(&mut args, "--unfold");
+    let plain = take_flag(&mut args, "--plain");
+    let frames = take_flag(&mut args, "--frames");
+    let source = input_source(&mut args);
+    if args.iter().any(|a| a.starts_with("--")) {
+        usage();
+    }
+    // a binary input is read as it is, not as text
+    let source = match if binary {
+        Ok(source)
+    } else {
+        source.buffered()
+    } {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("cannot read <stdin>: {}", e);
+            process::exit(1);
+        }
+    };
+    match args[..] {
+        ["list"] => {
+            for s in aoc::solutions() {
+                println!("{}  {}", s.name(), s.title());
+            }
+        }
+        ["run", id] => {
+            for s in selection(id) {
+                run(s, &source);
+            }
+        }
+        ["verify"] => verify(&aoc::solutions(), &source, answers_file),
+        ["verify", id] => verify(&selection(id), &source, answers_file),
+        ["bench", id] => {
+            let mut results = vec![];
+            for s in selection(id) {
+                if let Some(timings) = bench(s, &source, runs, baseline.as_ref()) {
+                    results.push((s.name(), timings));
+                }
+            }
+            if let Some(path) = save {
+                if let Err(e) = bench::save_baseline(Path::new(path), &results) {
+                    eprintln!("cannot write {}: {}", path, e);
+                    process::exit(1);
+                }
+            }
+        }
+        ["route"] => route(&source, factor, wrap as u32, ppm),
+        ["trace"] => trace(&source, binary),
+        ["add"] => add(&source, steps),
+        ["dice"] => dice(&source, die(seed, script)),
+        ["replay"] => replay(&source, unfold),
+        ["image", id, path] => {
+            let encoding = if plain {
+                Encoding::Plain
+            } else {
+                Encoding::Binary
+            };
+            image(&source, id, Path::new(path), encoding, frames)
+        }
+        _ => usage(),
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("usage: aoc2021 list");
+    eprintln!("       aoc2021 [<input>] run <day>[<part>]|all   (e.g. aoc2021 run 15b)");
+    eprintln!("       aoc2021 [<input>] verify [<day>[<part>]|all] [--answers <file>]");
+    eprintln!("       aoc2021 [<input>] bench <day>[<part>]|all [--runs <n>]");
+    eprintln!("               [--save <file>] [--baseline <file>]");
+    eprintln!("       aoc2021 [<input>] route [--factor <n>] [--wrap <n>] [--ppm <file>]");
+    eprintln!("               draw the cheapest route of day 15 on the tiled map");
+    eprintln!("       aoc2021 [<input>] trace [--binary]");
+    eprintln!("               show the expression of day 16 and how it evaluates,");
+    eprintln!("               --binary for a transmission in bytes instead of hex");
+    eprintln!("       aoc2021 [<input>] add [--steps]");
+    eprintln!("               add up the numbers of day 18 one by one,");
+    eprintln!("               --steps for every explode and split on the way");
+    eprintln!("       aoc2021 [<input>] dice [--seed <n> | --script <roll>,...]");
+    eprintln!("               play the practice game of day 21 turn by turn, with a");
+    eprintln!("               random die or one that repeats the rolls given, and a");
+    eprintln!("               player for each line of the input");
+    eprintln!("       aoc2021 [<input>] replay [--unfold]");
+    eprintln!("               show the cheapest way to organize the amphipods of day 23");
+    eprintln!("               move by move, --unfold for the deeper rooms of part two");
+    eprintln!("       aoc2021 [<input>] image 11a|13b|20a|20b <file> [--plain] [--frames]");
+    eprintln!("               write the octopuses, the folded sheet or the enhanced image");
+    eprintln!("               as a netpbm file, --frames for one file per step");
+    eprintln!();
+    eprintln!("input: --input <file>    read the puzzle input from <file>, '-' for stdin");
+    eprintln!("       --input-dir <dir> read inputNN.txt from <dir>");
+    eprintln!(
+        "       default is ${} if set, otherwise resources/input",
+        file::INPUT_DIR_VAR
+    );
+    process::exit(2);
+}
+
+/// Removes `--name <value>` from `args` and returns the value.
+fn take_option<'a>(args: &mut Vec<&'a str>, name: &str) -> Option<&'a str> {
+    let i = args.iter().position(|a| *a == name)?;
+    if i + 1 >= args.len() {
+        usage();
+    }
+    let value = args[i + 1];
+    args.drain(i..i + 2);
+    Some(value)
+}
+
+/// Removes `name` from `args` and tells whether it was there.
+fn take_flag(args: &mut Vec<&str>, name: &str) -> bool {
+    let found = args.contains(&name);
+    args.retain(|a| *a != name);
+    found
+}
+
+/// Removes the input options from `args` and returns the source they select.
+fn input_source(args: &mut Vec<&str>) -> InputSource {
+    if let Some(dir) = take_option(args, "--input-dir") {
+        return InputSource::Dir(PathBuf::from(dir));
+    }
+    match take_option(args, "--input") {
+        Some("-") => InputSource::Stdin,
+        Some(path) => InputSource::File(PathBuf::from(path)),
+        None => InputSource::default_dir(),
+    }
+}
+
+/// A number of at least 1.
+fn positive(value: &str) -> usize {
+    match value.parse() {
+        Ok(n) if n >= 1 => n,
+        _ => usage(),
+    }
+}
+
+/// The die for `dice`: random from `seed`, repeating `script`, or else the
+/// deterministic one of the puzzle.
+fn die(seed: Option<&str>, script: Option<&str>) -> Box<dyn Die> {
+    let sides = DiracGame::PRACTICE.sides;
+    match (seed, script) {
+        (None, None) => Box::new(DeterministicDie::new(sides)),
+        (Some(seed), None) => match seed.parse() {
+            Ok(seed) => Box::new(SeededDie::new(sides, seed)),
+            Err(_) => usage(),
+        },
+        (None, Some(script)) => {
+            let rolls = script.split(',').map(|r| positive(r) as u32).collect();
+            Box::new(ScriptedDie::new(rolls))
+        }
+        (Some(_), Some(_)) => usage(),
+    }
+}
+
+fn read_baseline(path: &str) -> Baseline {
+    let input = file::read_to_string(Path::new(path)).unwrap_or_else(|e| {
+        eprintln!("cannot read {}: {}", path, e);
+        process::exit(1);
+    });
+    bench::read_baseline(&input).unwrap_or_else(|e| {
+        eprintln!("{}", e.in_file(path));
+        process::exit(1);
+    })
+}
+
+/// The answers in `path`, or else next to the inputs. A missing file in the
+/// input directory just means that no answers are known yet.
+fn read_answers(path: Option<PathBuf>, source: &InputSource) -> Answers {
+    let (path, required) = match (path, source.answers_file()) {
+        (Some(path), _) => (path, true),
+        (None, Some(path)) => (path, false),
+        (None, None) => {
+            eprintln!("no answers for this input, use --answers <file>");
+            process::exit(2);
+        }
+    };
+    let input = match file::read_to_string(&path) {
+        Ok(input) => input,
+        Err(_) if !required && !path.exists() => return Answers::empty(),
+        Err(e) => {
+            eprintln!("cannot read {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    };
+    Answers::parse(&input).unwrap_or_else(|e| {
+        eprintln!("{}", e.in_file(&path.display().to_string()));
+        process::exit(1);
+    })
+}
+
+/// The puzzles named by `id`, or all of them; exits if there are none.
+fn selection(id: &str) -> Vec<&'static dyn Solution> {
+    let selected = if id == "all" {
+        aoc::solutions()
+    } else {
+        select(id)
+    };
+    if selected.is_empty() {
+        eprintln!("unknown puzzle '{}'", id);
+        process::exit(2);
+    }
+    selected
+}
+
+fn select(id: &str) -> Vec<&'static dyn Solution> {
+    let (day, part) = match id.strip_suffix(|c| c == 'a' || c == 'b') {
+        Some(day) => (day, id.chars().last()),
+        None => (id, None),
+    };
+    let day: u32 = match day.parse() {
+        Ok(day) => day,
+        Err(_) => return vec![],
+    };
+    aoc::solutions()
+        .into_iter()
+        .filter(|s| s.day() == day && part.is_none_or(|p| s.part() == p))
+        .collect()
+}
+
+fn run(s: &dyn Solution, source: &InputSource) {
+    if !source.has(&s.input_file()) {
+        println!("{}: no input", s.name());
+        return;
+    }
+    let result = match s.solve_from(source) {
+        Ok(answer) => answer.to_string(),
+        Err(e) => {
+            eprintln!("{}: {}", s.name(), e);
+            return;
+        }
+    };
+    if result.contains('\n') {
+        println!("{}:\n{}", s.name(), result);
+    } else {
+        println!("{}: {}", s.name(), result);
+    }
+}
+
+fn verify(selected: &[&dyn Solution], source: &InputSource, answers_file: Option<PathBuf>) {
+    let answers = read_answers(answers_file, source);
+    let (mut passed, mut failed, mut unknown) = (0, 0, 0);
+    for s in selected {
+        if !source.has(&s.input_file()) {
+            println!("{}: {}, no input", s.name(), Verdict::Unknown);
+            unknown += 1;
+            continue;
+        }
+        let actual = match s.solve_from(source) {
+            Ok(actual) => actual,
+            Err(e) => {
+                eprintln!("{}: {}", s.name(), e);
+                failed += 1;
+                continue;
+            }
+        };
+        let verdict = answers.verify(&s.name(), &actual);
+        match verdict {
+            Verdict::Pass => passed += 1,
+            Verdict::Fail { .. } => failed += 1,
+            Verdict::Unknown => unknown += 1,
+        }
+        if verdict == Verdict::Pass {
+            println!("{}: {}", s.name(), verdict);
+        } else {
+            println!("{}: {}, got {}", s.name(), verdict, actual);
+        }
+    }
+    println!("{} passed, {} failed, {} unknown", passed, failed, unknown);
+    if failed > 0 {
+        process::exit(1);
+    }
+}
+
+fn bench(
+    s: &dyn Solution,
+    source: &InputSource,
+    runs: usize,
+    baseline: Option<&Baseline>,
+) -> Option<bench::Timings> {
+    if !source.has(&s.input_file()) {
+        println!("{}: no input", s.name());
+        return None;
+    }
+    let name = source.name(&s.input_file());
+    let input = match source.read(&s.input_file()) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("{}: cannot read {}: {}", s.name(), name, e);
+            return None;
+        }
+    };
+    match bench::bench(s, &input, runs) {
+        Ok(timings) => {
+            let before = baseline.and_then(|b| b.get(&s.name()));
+            println!("{}", bench::report(&s.name(), &timings, before));
+            Some(timings)
+        }
+        Err(e) => {
+            eprintln!("{}: {}", s.name(), e.in_file(&name));
+            None
+        }
+    }
+}
+
+/// Prints the cheapest route of day 15 over its map, or writes it to `ppm` as
+/// an image.
+fn route(source: &InputSource, factor: usize, wrap: u32, ppm: Option<&str>) {
+    let input_file = Day15b.input_file();
+    let name = source.name(&input_file);
+    let input = source.read(&input_file).unwrap_or_else(|e| {
+        eprintln!("cannot read {}: {}", name, e);
+        process::exit(1);
+    });
+    let output = day15b::export_route(&input, factor, wrap, ppm.is_some()).unwrap_or_else(|e| {
+        eprintln!("{}", e.in_file(&name));
+        process::exit(1);
+    });
+    let written = match ppm {
+        Some(path) => fs::write(path, output).map_err(|e| (path, e)),
+        None => io::stdout().write_all(&output).map_err(|e| ("<stdout>", e)),
+    };
+    if let Err((path, e)) = written {
+        eprintln!("cannot write {}: {}", path, e);
+        process::exit(1);
+    }
+}
+
+fn trace(source: &InputSource, binary: bool) {
+    let input_file = Day16b.input_file();
+    let name = source.name(&input_file);
+    let explained = if binary && *source == InputSource::Stdin {
+        source.open(&input_file).map(day16b::explain_stream)
+    } else if binary {
+        source
+            .read_bytes(&input_file)
+            .map(|bytes| day16b::explain_binary(&bytes))
+    } else {
+        source
+            .read(&input_file)
+            .map(|input| day16b::explain(&input))
+    };
+    match explained {
+        Ok(Ok(explained)) => print!("{}", explained),
+        Ok(Err(e)) => {
+            eprintln!("{}", e.in_file(&name));
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("cannot read {}: {}", name, e);
+            process::exit(1);
+        }
+    }
+}
+
+fn add(source: &InputSource, steps: bool) {
+    let input_file = Day18a.input_file();
+    let name = source.name(&input_file);
+    let input = source.read(&input_file).unwrap_or_else(|e| {
+        eprintln!("cannot read {}: {}", name, e);
+        process::exit(1);
+    });
+    match day18a::explain_sum(&input, steps) {
+        Ok(explained) => print!("{}", explained),
+        Err(e) => {
+            eprintln!("{}", e.in_file(&name));
+            process::exit(1);
+        }
+    }
+}
+
+fn dice(source: &InputSource, die: Box<dyn Die>) {
+    let input_file = Day21a.input_file();
+    let name = source.name(&input_file);
+    let input = source.read(&input_file).unwrap_or_else(|e| {
+        eprintln!("cannot read {}: {}", name, e);
+        process::exit(1);
+    });
+    match day21a::practice(&input, die) {
+        Ok(played) => print!("{}", played),
+        Err(e) => {
+            eprintln!("{}", e.in_file(&name));
+            process::exit(1);
+        }
+    }
+}
+
+fn replay(source: &InputSource, unfold: bool) {
+    let input_file = Day23a.input_file();
+    let name = source.name(&input_file);
+    let input = source.read(&input_file).unwrap_or_else(|e| {
+        eprintln!("cannot read {}: {}", name, e);
+        process::exit(1);
+    });
+    match day23a::replay(&input, unfold) {
+        Ok(replayed) => print!("{}", replayed),
+        Err(e) => {
+            eprintln!("{}", e.in_file(&name));
+            process::exit(1);
+        }
+    }
+}
+
+/// Writes the last picture of puzzle `id` to `path`, or else every one of
+/// them to a file of its own.
+fn image(source: &InputSource, id: &str, path: &Path, encoding: Encoding, all_frames: bool) {
+    let s = match select(id)[..] {
+        [s] => s,
+        _ => {
+            eprintln!("no image for puzzle '{}'", id);
+            process::exit(2);
+        }
+    };
+    let input_file = s.input_file();
+    let name = source.name(&input_file);
+    let input = source.read(&input_file).unwrap_or_else(|e| {
+        eprintln!("cannot read {}: {}", name, e);
+        process::exit(1);
+    });
+    let pbm = |frames: Vec<_>| frames.iter().map(|g| netpbm::pbm(g, encoding)).collect();
+    let frames: Result<Vec<Vec<u8>>, _> = match s.name().as_str() {
+        "11a" => day11a::frames(&input, 100)
+            .map(|frames| frames.iter().map(|g| netpbm::pgm(g, 9, encoding)).collect()),
+        "13b" => day13b::frames(&input).map(pbm),
+        "20a" => day20a::frames(&input, 2).map(pbm),
+        "20b" => day20a::frames(&input, 50).map(pbm),
+        _ => {
+            eprintln!("no image for puzzle '{}'", id);
+            process::exit(2);
+        }
+    };
+    let frames = frames.unwrap_or_else(|e| {
+        eprintln!("{}", e.in_file(&name));
+        process::exit(1);
+    });
+    let written: Vec<(PathBuf, &Vec<u8>)> = if all_frames {
+        frames
+            .iter()
+            .enumerate()
+            .map(|(n, frame)| (netpbm::frame_path(path, n), frame))
+            .collect()
+    } else {
+        vec![(path.to_path_buf(), frames.last().unwrap())]
+    };
+    for (path, frame) in written {
+        if let Err(e) = fs::write(&path, frame) {
+            eprintln!("cannot write {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    }
+}